@@ -0,0 +1,382 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::{anyhow, Context, Result};
+use chrono_tz::Tz;
+use serde_json::json;
+
+use crate::{
+    compare::{MatchMode, MatchOptions, MatchWindow},
+    deserialize::{merge_imported, IntuitMode},
+    handlers::AppState,
+    overrides::Overrides,
+    roster::Roster,
+    settings::Settings,
+    timezone::{parse_timezone, DstPolicy, DEFAULT_TIMEZONE},
+    util::{get_orders, get_path, get_timesheet, process_payroll},
+};
+
+const USAGE: &str = "Usage: payroll_cli --caterease <FILE>... --intuit <FILE>... --output <FILE> [--precision <HOURS>] [--before <MINUTES>] [--after <MINUTES>] [--match-mode <greedy|optimal>] [--roster <FILE>] [--settings <FILE>] [--fuzzy <0-1>] [--timezone <IANA NAME>] [--dst <earliest|latest|flag>] [--intuit-mode <shifts|jobs>] [--period-days <DAYS>] [--block-period-mismatch] [--overwrite]";
+
+/// Exit code for bad or missing arguments
+const EXIT_USAGE: u8 = 2;
+/// Exit code for a failed payroll run
+const EXIT_FAILURE: u8 = 1;
+
+#[derive(Debug, PartialEq)]
+pub struct CliArgs {
+    /// Caterease exports, merged in the order given
+    pub caterease: Vec<String>,
+    /// Intuit exports, merged in the order given
+    pub intuit: Vec<String>,
+    pub output: PathBuf,
+    pub window: MatchWindow,
+    pub match_mode: MatchMode,
+    pub roster: Option<PathBuf>,
+    /// The app's settings.json, for its column synonyms and roster
+    pub settings: Option<PathBuf>,
+    pub fuzzy_threshold: Option<f64>,
+    /// Timezone the exports' local times are in
+    pub timezone: Tz,
+    pub dst_policy: DstPolicy,
+    pub intuit_mode: IntuitMode,
+    /// Days the inputs' pay periods may differ by before it's reported
+    pub period_tolerance_days: i64,
+    /// Fail instead of warning when the pay periods differ
+    pub block_period_mismatch: bool,
+    pub overwrite: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<CliArgs> {
+    let mut caterease = Vec::new();
+    let mut intuit = Vec::new();
+    let mut output = None;
+    let mut window = MatchWindow::from_hours(1);
+    let mut before = None;
+    let mut after = None;
+    let mut match_mode = MatchMode::default();
+    let mut roster = None;
+    let mut settings = None;
+    let mut fuzzy_threshold = None;
+    let mut timezone = parse_timezone(DEFAULT_TIMEZONE)?;
+    let mut dst_policy = DstPolicy::default();
+    let mut intuit_mode = IntuitMode::default();
+    let mut period_tolerance_days = Settings::default().period_tolerance_days;
+    let mut block_period_mismatch = false;
+    let mut overwrite = false;
+
+    let mut iter = args.iter();
+    while let Some(flag) = iter.next() {
+        let mut value = || {
+            iter.next()
+                .cloned()
+                .ok_or_else(|| anyhow!("Missing value for {}", flag))
+        };
+
+        match flag.as_str() {
+            "--caterease" => caterease.push(value()?),
+            "--intuit" => intuit.push(value()?),
+            "--output" => output = Some(PathBuf::from(value()?)),
+            "--precision" => {
+                let precision = value()?
+                    .parse::<i64>()
+                    .context("Precision must be a whole number of hours")?;
+                if precision < 1 {
+                    return Err(anyhow!("Precision must be at least 1 hour"));
+                }
+                window = MatchWindow::from_hours(precision);
+            }
+            "--before" => before = Some(parse_minutes(&value()?)?),
+            "--after" => after = Some(parse_minutes(&value()?)?),
+            "--match-mode" => {
+                match_mode = match value()?.as_str() {
+                    "greedy" => MatchMode::Greedy,
+                    "optimal" => MatchMode::Optimal,
+                    other => return Err(anyhow!("Unknown match mode: {}", other)),
+                }
+            }
+            "--roster" => roster = Some(PathBuf::from(value()?)),
+            "--settings" => settings = Some(PathBuf::from(value()?)),
+            "--fuzzy" => {
+                let threshold = value()?
+                    .parse::<f64>()
+                    .context("Fuzzy threshold must be a number")?;
+                if !(0.0..=1.0).contains(&threshold) {
+                    return Err(anyhow!("Fuzzy threshold must be between 0 and 1"));
+                }
+                fuzzy_threshold = Some(threshold);
+            }
+            "--timezone" => timezone = parse_timezone(&value()?)?,
+            "--dst" => {
+                dst_policy = match value()?.as_str() {
+                    "earliest" => DstPolicy::Earliest,
+                    "latest" => DstPolicy::Latest,
+                    "flag" => DstPolicy::Flag,
+                    other => return Err(anyhow!("Unknown DST policy: {}", other)),
+                }
+            }
+            "--intuit-mode" => {
+                intuit_mode = match value()?.as_str() {
+                    "shifts" => IntuitMode::Shifts,
+                    "jobs" => IntuitMode::Jobs,
+                    other => return Err(anyhow!("Unknown Intuit mode: {}", other)),
+                }
+            }
+            "--period-days" => {
+                period_tolerance_days = value()?
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|days| *days >= 0)
+                    .context("Period days must be a whole number of days")?;
+            }
+            "--block-period-mismatch" => block_period_mismatch = true,
+            "--overwrite" => overwrite = true,
+            other => return Err(anyhow!("Unknown argument: {}", other)),
+        }
+    }
+
+    // Explicit minute bounds take priority over --precision, whatever the flag order
+    if let Some(before) = before {
+        window.before_minutes = before;
+    }
+    if let Some(after) = after {
+        window.after_minutes = after;
+    }
+
+    if caterease.is_empty() {
+        return Err(anyhow!("Missing --caterease"));
+    }
+    if intuit.is_empty() {
+        return Err(anyhow!("Missing --intuit"));
+    }
+
+    Ok(CliArgs {
+        caterease,
+        intuit,
+        output: output.context("Missing --output")?,
+        window,
+        match_mode,
+        roster,
+        settings,
+        fuzzy_threshold,
+        timezone,
+        dst_policy,
+        intuit_mode,
+        period_tolerance_days,
+        block_period_mismatch,
+        overwrite,
+    })
+}
+
+fn parse_minutes(value: &str) -> Result<i64> {
+    let minutes = value
+        .parse::<i64>()
+        .context("Window bounds must be whole minutes")?;
+    if minutes < 0 {
+        return Err(anyhow!("Window bounds can't be negative"));
+    }
+
+    Ok(minutes)
+}
+
+pub fn run_pipeline(args: &CliArgs) -> Result<serde_json::Value> {
+    for file in &args.caterease {
+        get_path(file).context("Caterease file")?;
+    }
+    for file in &args.intuit {
+        get_path(file).context("Intuit file")?;
+    }
+
+    // Overrides saved by the app next to the first Caterease file are applied here too
+    let overrides = Overrides::load(&Overrides::sidecar_path(Path::new(&args.caterease[0])))?;
+
+    // Saved settings only supply the column synonyms and roster, the flags decide the rest
+    let saved = match &args.settings {
+        Some(path) => load_settings(path)?,
+        None => Settings::default(),
+    };
+    let roster_path = args.roster.as_ref().or(saved.roster_path.as_ref());
+
+    let settings = Settings {
+        column_synonyms: saved.column_synonyms,
+        timezone: args.timezone.name().to_string(),
+        dst_policy: args.dst_policy,
+        intuit_mode: args.intuit_mode,
+        period_tolerance_days: args.period_tolerance_days,
+        block_period_mismatch: args.block_period_mismatch,
+        ..Default::default()
+    };
+
+    let mut state = AppState::default();
+    for file in &args.caterease {
+        let orders = get_orders(file, &settings)?;
+        merge_imported(&mut state.caterease, &mut state.caterease_issues, orders);
+    }
+    for file in &args.intuit {
+        let timesheets = get_timesheet(file, &settings)?;
+        merge_imported(&mut state.intuit, &mut state.intuit_issues, timesheets);
+    }
+
+    let state = AppState {
+        roster: match roster_path {
+            Some(path) => Roster::load(path)?,
+            None => Roster::default(),
+        },
+        overrides,
+        settings,
+        ..state
+    };
+
+    let options = MatchOptions {
+        window: args.window,
+        mode: args.match_mode,
+        fuzzy_threshold: args.fuzzy_threshold,
+    };
+
+    let result = process_payroll(&options, &state, &args.output, args.overwrite)?;
+
+    Ok(json!(result))
+}
+
+/// Unlike the app, a settings file that can't be read is an error rather than the defaults
+fn load_settings(path: &Path) -> Result<Settings> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Failed to open settings: {}", path.to_string_lossy()))?;
+
+    serde_json::from_str(&contents).context("Settings file is not valid JSON")
+}
+
+/// Entry point for the headless binary. Prints the run summary as JSON on success.
+pub fn run_cli() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if args.iter().any(|a| a == "--help" || a == "-h") {
+        println!("{}", USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let parsed = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match run_pipeline(&parsed) {
+        Ok(summary) => {
+            println!("{}", summary);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_all_flags() {
+        let args = to_args(&[
+            "--caterease",
+            "week1.xlsx",
+            "--intuit",
+            "times.xlsx",
+            "--caterease",
+            "week2.csv",
+            "--output",
+            "out.xlsx",
+            "--precision",
+            "3",
+            "--after",
+            "15",
+            "--match-mode",
+            "greedy",
+            "--roster",
+            "roster.json",
+            "--settings",
+            "settings.json",
+            "--fuzzy",
+            "0.85",
+            "--timezone",
+            "America/Denver",
+            "--dst",
+            "flag",
+            "--intuit-mode",
+            "jobs",
+            "--period-days",
+            "1",
+            "--block-period-mismatch",
+            "--overwrite",
+        ]);
+
+        let parsed = parse_args(&args).unwrap();
+        assert_eq!(
+            parsed,
+            CliArgs {
+                caterease: vec!["week1.xlsx".to_string(), "week2.csv".to_string()],
+                intuit: vec!["times.xlsx".to_string()],
+                output: PathBuf::from("out.xlsx"),
+                window: MatchWindow {
+                    before_minutes: 180,
+                    after_minutes: 15,
+                },
+                match_mode: MatchMode::Greedy,
+                roster: Some(PathBuf::from("roster.json")),
+                settings: Some(PathBuf::from("settings.json")),
+                fuzzy_threshold: Some(0.85),
+                timezone: chrono_tz::America::Denver,
+                dst_policy: DstPolicy::Flag,
+                intuit_mode: IntuitMode::Jobs,
+                period_tolerance_days: 1,
+                block_period_mismatch: true,
+                overwrite: true,
+            }
+        );
+    }
+
+    #[test]
+    fn window_defaults_to_one_hour() {
+        let args = to_args(&[
+            "--caterease",
+            "a.xlsx",
+            "--intuit",
+            "b.xlsx",
+            "--output",
+            "c.xlsx",
+        ]);
+        assert_eq!(
+            parse_args(&args).unwrap().window,
+            MatchWindow::from_hours(1)
+        );
+    }
+
+    #[test]
+    fn rejects_missing_and_unknown_args() {
+        assert!(parse_args(&to_args(&["--caterease", "a.xlsx"])).is_err());
+        assert!(parse_args(&to_args(&["--intuit"])).is_err());
+        assert!(parse_args(&to_args(&["--bogus", "x"])).is_err());
+        assert!(parse_args(&to_args(&[
+            "--caterease",
+            "a",
+            "--intuit",
+            "b",
+            "--output",
+            "c",
+            "--precision",
+            "0"
+        ]))
+        .is_err());
+    }
+}
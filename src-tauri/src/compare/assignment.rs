@@ -0,0 +1,133 @@
+/// Solves the rectangular assignment problem. `costs[row][col]` is `None` when the pair is not
+/// allowed. Returns, for every row, the column it was assigned to.
+///
+/// The result matches as many rows as possible, and among those assignments picks the one with
+/// the lowest total cost.
+pub fn min_cost_assignment(costs: &[Vec<Option<i64>>]) -> Vec<Option<usize>> {
+    let rows = costs.len();
+    let cols = costs.first().map_or(0, |r| r.len());
+    if rows == 0 || cols == 0 {
+        return vec![None; rows];
+    }
+
+    // The solver below needs at least as many columns as rows
+    if rows > cols {
+        let transposed: Vec<Vec<Option<i64>>> = (0..cols)
+            .map(|c| (0..rows).map(|r| costs[r][c]).collect())
+            .collect();
+
+        let mut result = vec![None; rows];
+        for (col, row) in min_cost_assignment(&transposed).into_iter().enumerate() {
+            if let Some(row) = row {
+                result[row] = Some(col);
+            }
+        }
+        return result;
+    }
+
+    // Forbidden pairs cost more than any set of allowed pairs combined, so the solver only
+    // uses them when a row has nothing else left
+    let max_cost = costs.iter().flatten().flatten().copied().max().unwrap_or(0);
+    let forbidden = (max_cost + 1) * (rows as i64 + 1);
+    let cost = |r: usize, c: usize| costs[r][c].unwrap_or(forbidden);
+
+    // Hungarian algorithm with potentials (1-indexed, column 0 is a sentinel)
+    let mut u = vec![0i64; rows + 1];
+    let mut v = vec![0i64; cols + 1];
+    let mut owner = vec![0usize; cols + 1];
+    let mut way = vec![0usize; cols + 1];
+
+    for row in 1..=rows {
+        owner[0] = row;
+        let mut col0 = 0;
+        let mut min_v = vec![i64::MAX; cols + 1];
+        let mut used = vec![false; cols + 1];
+
+        loop {
+            used[col0] = true;
+            let row0 = owner[col0];
+            let mut delta = i64::MAX;
+            let mut col1 = 0;
+
+            for col in 1..=cols {
+                if used[col] {
+                    continue;
+                }
+                let reduced = cost(row0 - 1, col - 1) - u[row0] - v[col];
+                if reduced < min_v[col] {
+                    min_v[col] = reduced;
+                    way[col] = col0;
+                }
+                if min_v[col] < delta {
+                    delta = min_v[col];
+                    col1 = col;
+                }
+            }
+
+            for col in 0..=cols {
+                if used[col] {
+                    u[owner[col]] += delta;
+                    v[col] -= delta;
+                } else {
+                    min_v[col] -= delta;
+                }
+            }
+
+            col0 = col1;
+            if owner[col0] == 0 {
+                break;
+            }
+        }
+
+        loop {
+            let col1 = way[col0];
+            owner[col0] = owner[col1];
+            col0 = col1;
+            if col0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut result = vec![None; rows];
+    for col in 1..=cols {
+        let row = owner[col];
+        if row != 0 && costs[row - 1][col - 1].is_some() {
+            result[row - 1] = Some(col - 1);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_lowest_total_cost() {
+        // Greedy would give row 0 column 0 (cost 1) and leave row 1 with cost 10
+        let costs = vec![vec![Some(1), Some(2)], vec![Some(2), Some(10)]];
+        assert_eq!(min_cost_assignment(&costs), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn prefers_more_matches_over_lower_cost() {
+        // Row 0 could take column 0 cheaply, but then row 1 would be left without a match
+        let costs = vec![vec![Some(1), Some(50)], vec![Some(2), None]];
+        assert_eq!(min_cost_assignment(&costs), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn handles_more_rows_than_columns() {
+        let costs = vec![vec![Some(5)], vec![Some(1)], vec![None]];
+        assert_eq!(min_cost_assignment(&costs), vec![None, Some(0), None]);
+    }
+
+    #[test]
+    fn forbidden_pairs_stay_unassigned() {
+        let costs = vec![vec![None, None], vec![None, Some(3)]];
+        assert_eq!(min_cost_assignment(&costs), vec![None, Some(1)]);
+        assert!(min_cost_assignment(&[]).is_empty());
+    }
+}
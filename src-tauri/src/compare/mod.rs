@@ -1,6 +1,8 @@
+mod assignment;
 mod operations;
 mod types;
 mod util;
 
 pub use operations::*;
 pub use types::*;
+pub use util::is_valid_order;
@@ -1,56 +1,162 @@
+use std::collections::VecDeque;
+
 use crate::{
     compare::{
-        util::{is_name_match, is_valid_order, is_within_time},
-        PreparedRow, ReferenceResult,
+        assignment::min_cost_assignment,
+        util::{is_valid_order, is_within_time, match_employee},
+        MatchMode, MatchOptions, MatchOutcome, NameMatch, PreparedRow, ReferenceResult,
     },
     deserialize::{Order, TimeActivity},
+    overrides::Overrides,
+    roster::Roster,
 };
 
+/// A time activity that could belong to an order
+struct Candidate {
+    /// Position in the timesheet
+    index: usize,
+    /// Clock-in minus the order's ready time, in seconds
+    offset: i64,
+    name_match: NameMatch,
+}
+
+/// Everything the timesheet offers a single valid order
+#[derive(Default)]
+struct OrderCandidates {
+    /// Free shifts that pass both the name and time checks, in timesheet order
+    available: Vec<Candidate>,
+    /// Shifts whose name matches, at any time
+    name_hits: usize,
+    /// Shifts whose name matches inside the window, including taken but not ignored ones
+    in_window: usize,
+    /// Shifts whose name matches inside the window but were ignored through the overrides
+    ignored: usize,
+    /// Offset in seconds of the closest name match, used to explain misses
+    nearest_offset: Option<i64>,
+}
+
 pub fn cross_reference_orders(
     orders: &mut [Order],
-    time_sheets: &mut [TimeActivity],
-    precision: i64,
+    mut time_sheets: Vec<TimeActivity>,
+    options: &MatchOptions,
+    roster: &Roster,
+    overrides: &Overrides,
 ) -> ReferenceResult {
+    // Manual overrides go first, so the matcher never hands out ignored or pinned shifts
+    for time_activity in time_sheets.iter_mut() {
+        time_activity.ignored = overrides.is_ignored(time_activity);
+    }
+
+    let mut pinned: Vec<Option<usize>> = vec![None; orders.len()];
+    for (i, order) in orders.iter().enumerate() {
+        let Some(key) = overrides.pin_for(order) else {
+            continue;
+        };
+
+        // A pin whose shift is gone from the timesheet falls back to normal matching
+        if let Some(index) = time_sheets
+            .iter()
+            .position(|time_activity| !time_activity.matched && key.matches(time_activity))
+        {
+            time_sheets[index].matched = true;
+            pinned[i] = Some(index);
+        }
+    }
+
+    let forced: Vec<bool> = orders
+        .iter()
+        .map(|order| overrides.is_forced_unmatched(order))
+        .collect();
+
+    let mut candidates = find_candidates(orders, &time_sheets, options, roster);
+    for (i, found) in candidates.iter_mut().enumerate() {
+        if let Some(found) = found.as_mut().filter(|_| pinned[i].is_some() || forced[i]) {
+            found.available.clear();
+        }
+    }
+
+    let assigned = match options.mode {
+        MatchMode::Greedy => assign_greedy(&candidates, time_sheets.len()),
+        MatchMode::Optimal => assign_optimal(&candidates, time_sheets.len()),
+    };
+
     let mut rows: Vec<PreparedRow> = Vec::new();
     let mut matched = 0;
     let mut skipped = 0;
 
-    for order in orders.iter() {
+    for (i, order) in orders.iter().enumerate() {
         let mut entry = PreparedRow {
             order: order.clone(),
             hours: 0.0,
             miles: 0.0,
             suggested_in: None,
             suggested_out: None,
+            shift: None,
+            name_match: None,
+            outcome: MatchOutcome::SkippedInvalidDriver,
+            delta_minutes: None,
+            candidate_count: 0,
         };
 
-        let lower_emp = order.employee.to_lowercase();
-        if !is_valid_order(&lower_emp) {
-            // Patio party or something
+        if let Some(index) = pinned[i] {
+            let time_activity = &time_sheets[index];
+
+            entry.outcome = MatchOutcome::Pinned;
+            entry.delta_minutes = order
+                .datetime
+                .map(|datetime| (time_activity.in_time - datetime).num_seconds() as f64 / 60.0);
+            fill_from_shift(&mut entry, time_activity);
+
             rows.push(entry);
-            skipped += 1;
+            matched += 1;
             continue;
         }
 
-        for time_activity in time_sheets.iter_mut() {
-            if time_activity.matched {
-                continue;
-            }
+        if forced[i] {
+            entry.outcome = MatchOutcome::ForcedUnmatched;
+            rows.push(entry);
+            continue;
+        }
 
-            if !is_name_match(&lower_emp, time_activity, order.expanded) {
-                continue;
+        let Some(found) = &candidates[i] else {
+            // Patio party or something, or an order with nothing to match by time
+            if order.datetime.is_none() && is_valid_order(&order.employee.to_lowercase()) {
+                entry.outcome = MatchOutcome::MissingReadyTime;
             }
+            rows.push(entry);
+            skipped += 1;
+            continue;
+        };
 
-            if is_within_time(&order.datetime, &time_activity.in_time, precision) {
-                entry.hours = time_activity.hours;
-                entry.miles = time_activity.miles;
-                entry.suggested_in = Some(time_activity.in_time);
-                entry.suggested_out = Some(time_activity.out_time);
+        entry.candidate_count = found.in_window;
+
+        let chosen = assigned[i].and_then(|index| {
+            found
+                .available
+                .iter()
+                .find(|candidate| candidate.index == index)
+        });
+
+        match chosen {
+            Some(candidate) => {
+                let time_activity = &mut time_sheets[candidate.index];
+
+                entry.outcome = MatchOutcome::Matched;
+                entry.delta_minutes = Some(candidate.offset as f64 / 60.0);
+                entry.name_match = Some(candidate.name_match);
+                fill_from_shift(&mut entry, time_activity);
 
                 matched += 1;
                 time_activity.matched = true;
-
-                break;
+            }
+            None => {
+                entry.outcome = match (found.name_hits, found.in_window) {
+                    (0, _) => MatchOutcome::NoNameCandidate,
+                    (_, 0) if found.ignored > 0 => MatchOutcome::ShiftIgnored,
+                    (_, 0) => MatchOutcome::NameButOutsideWindow,
+                    _ => MatchOutcome::ShiftAlreadyConsumed,
+                };
+                entry.delta_minutes = found.nearest_offset.map(|offset| offset as f64 / 60.0);
             }
         }
 
@@ -61,5 +167,537 @@ pub fn cross_reference_orders(
         rows,
         matched,
         skipped,
+        time_sheets,
+    }
+}
+
+fn fill_from_shift(entry: &mut PreparedRow, time_activity: &TimeActivity) {
+    entry.hours = time_activity.hours;
+    entry.miles = time_activity.miles;
+    entry.suggested_in = Some(time_activity.in_time);
+    entry.suggested_out = Some(time_activity.out_time);
+    entry.shift = time_activity.shift;
+}
+
+/// Surveys the timesheet for each order. Invalid orders and orders without a ready time
+/// get `None`.
+fn find_candidates(
+    orders: &[Order],
+    time_sheets: &[TimeActivity],
+    options: &MatchOptions,
+    roster: &Roster,
+) -> Vec<Option<OrderCandidates>> {
+    orders
+        .iter()
+        .map(|order| {
+            let lower_emp = order.employee.to_lowercase();
+            if !is_valid_order(&lower_emp) {
+                return None;
+            }
+            let datetime = order.datetime?;
+
+            let usernames = roster.resolve(&lower_emp);
+            let mut found = OrderCandidates::default();
+
+            for (index, time_activity) in time_sheets.iter().enumerate() {
+                let Some(name_match) = match_employee(
+                    &lower_emp,
+                    &usernames,
+                    time_activity,
+                    order.expanded,
+                    options.fuzzy_threshold,
+                ) else {
+                    continue;
+                };
+
+                let offset = (time_activity.in_time - datetime).num_seconds();
+
+                found.name_hits += 1;
+                if found
+                    .nearest_offset
+                    .is_none_or(|nearest| offset.abs() < nearest.abs())
+                {
+                    found.nearest_offset = Some(offset);
+                }
+
+                if !is_within_time(&datetime, &time_activity.in_time, &options.window) {
+                    continue;
+                }
+                if time_activity.ignored {
+                    found.ignored += 1;
+                    continue;
+                }
+                found.in_window += 1;
+
+                if !time_activity.matched {
+                    found.available.push(Candidate {
+                        index,
+                        offset,
+                        name_match,
+                    });
+                }
+            }
+
+            Some(found)
+        })
+        .collect()
+}
+
+/// Each order, in file order, takes the first candidate nobody has claimed yet
+fn assign_greedy(
+    candidates: &[Option<OrderCandidates>],
+    activity_count: usize,
+) -> Vec<Option<usize>> {
+    let mut taken = vec![false; activity_count];
+
+    candidates
+        .iter()
+        .map(|found| {
+            let candidate = found
+                .iter()
+                .flat_map(|found| &found.available)
+                .find(|c| !taken[c.index])?;
+            taken[candidate.index] = true;
+            Some(candidate.index)
+        })
+        .collect()
+}
+
+/// Splits orders and activities into groups that share candidates (in practice, one group per
+/// driver) and solves each group as a minimum-distance bipartite matching. Weaker name rules
+/// carry a penalty larger than any total distance, so a fuzzy pair never beats an exact one
+fn assign_optimal(
+    candidates: &[Option<OrderCandidates>],
+    activity_count: usize,
+) -> Vec<Option<usize>> {
+    let mut assigned = vec![None; candidates.len()];
+
+    // Reverse edges so a group can be grown from either side
+    let mut orders_for_activity: Vec<Vec<usize>> = vec![Vec::new(); activity_count];
+    for (order, found) in candidates.iter().enumerate() {
+        for candidate in found.iter().flat_map(|found| &found.available) {
+            orders_for_activity[candidate.index].push(order);
+        }
+    }
+
+    let mut order_seen = vec![false; candidates.len()];
+    let mut activity_seen = vec![false; activity_count];
+
+    for start in 0..candidates.len() {
+        if order_seen[start]
+            || candidates[start]
+                .as_ref()
+                .is_none_or(|c| c.available.is_empty())
+        {
+            continue;
+        }
+
+        let mut group_orders = Vec::new();
+        let mut group_activities = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        order_seen[start] = true;
+
+        while let Some(order) = queue.pop_front() {
+            group_orders.push(order);
+
+            for candidate in candidates[order].iter().flat_map(|found| &found.available) {
+                let index = candidate.index;
+                if activity_seen[index] {
+                    continue;
+                }
+                activity_seen[index] = true;
+                group_activities.push(index);
+
+                for next in &orders_for_activity[index] {
+                    if !order_seen[*next] {
+                        order_seen[*next] = true;
+                        queue.push_back(*next);
+                    }
+                }
+            }
+        }
+
+        // Keep file order inside the group so ties resolve the same way every run
+        group_orders.sort_unstable();
+        group_activities.sort_unstable();
+
+        let group_candidates = || {
+            group_orders
+                .iter()
+                .flat_map(|order| candidates[*order].iter().flat_map(|found| &found.available))
+        };
+        let farthest = group_candidates()
+            .map(|candidate| candidate.offset.abs())
+            .max()
+            .unwrap_or(0);
+        let penalty = (farthest + 1) * (group_orders.len() as i64 + 1);
+
+        let costs: Vec<Vec<Option<i64>>> = group_orders
+            .iter()
+            .map(|order| {
+                group_activities
+                    .iter()
+                    .map(|activity| {
+                        candidates[*order]
+                            .iter()
+                            .flat_map(|found| &found.available)
+                            .find(|candidate| candidate.index == *activity)
+                            .map(|candidate| {
+                                candidate.name_match.rule.rank() * penalty + candidate.offset.abs()
+                            })
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for (row, col) in min_cost_assignment(&costs).into_iter().enumerate() {
+            assigned[group_orders[row]] = col.map(|col| group_activities[col]);
+        }
+    }
+
+    assigned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compare::{MatchWindow, NameRule},
+        deserialize::{RecordSource, Role, ShiftSpan},
+        overrides::{OrderKey, Pin, ShiftKey},
+        roster::Employee,
+    };
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 3, hour, minute, 0).unwrap()
+    }
+
+    fn order(employee: &str, datetime: DateTime<Utc>) -> Order {
+        Order {
+            date: Some(datetime.date_naive()),
+            employee: employee.to_string(),
+            client: "".to_string(),
+            description: "".to_string(),
+            count: 0,
+            grat: 0.0,
+            origin: "".to_string(),
+            event: "".to_string(),
+            ready: Some(datetime.time()),
+            total: 0.0,
+            datetime: Some(datetime),
+            expanded: false,
+            role: Role::Lead,
+            source: RecordSource::default(),
+        }
+    }
+
+    fn shift(last: &str, in_time: DateTime<Utc>, hours: f64) -> TimeActivity {
+        TimeActivity {
+            first_name: "Test".to_string(),
+            last_name: last.to_string(),
+            username: last.to_lowercase(),
+            in_time,
+            out_time: in_time,
+            hours,
+            miles: 0.0,
+            customer: String::new(),
+            shift: None,
+            matched: false,
+            ignored: false,
+            source: RecordSource::default(),
+        }
+    }
+
+    fn options(mode: MatchMode) -> MatchOptions {
+        MatchOptions {
+            window: MatchWindow::from_hours(2),
+            mode,
+            fuzzy_threshold: None,
+        }
+    }
+
+    /// The first order is an hour from either shift, but only the later shift fits the second order
+    fn contested_inputs() -> (Vec<Order>, Vec<TimeActivity>) {
+        let orders = vec![order("Ann Lee", at(11, 0)), order("Ann Lee", at(12, 30))];
+        let shifts = vec![shift("Lee", at(10, 0), 1.0), shift("Lee", at(12, 0), 2.0)];
+        (orders, shifts)
+    }
+
+    #[test]
+    fn greedy_lets_early_order_steal_later_shift() {
+        let (mut orders, mut shifts) = contested_inputs();
+        // Reverse shift order so the first order sees the later shift first
+        shifts.reverse();
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &options(MatchMode::Greedy),
+            &Roster::default(),
+            &Overrides::default(),
+        );
+        assert_eq!(result.rows[0].hours, 2.0);
+        assert_eq!(result.rows[1].hours, 0.0);
+        assert_eq!(result.matched, 1);
+    }
+
+    #[test]
+    fn optimal_matches_every_order_to_nearest_shift() {
+        let (mut orders, mut shifts) = contested_inputs();
+        shifts.reverse();
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &options(MatchMode::Optimal),
+            &Roster::default(),
+            &Overrides::default(),
+        );
+        assert_eq!(result.rows[0].hours, 1.0);
+        assert_eq!(result.rows[1].hours, 2.0);
+        assert_eq!(result.matched, 2);
+        assert!(result.time_sheets.iter().all(|s| s.matched));
+    }
+
+    #[test]
+    fn invalid_orders_are_skipped_in_both_modes() {
+        for mode in [MatchMode::Greedy, MatchMode::Optimal] {
+            let mut orders = vec![order("Patio Party", at(11, 0))];
+            let shifts = vec![shift("Party", at(11, 0), 1.0)];
+
+            let result = cross_reference_orders(
+                &mut orders,
+                shifts,
+                &options(mode),
+                &Roster::default(),
+                &Overrides::default(),
+            );
+            assert_eq!(result.skipped, 1);
+            assert_eq!(result.matched, 0);
+        }
+    }
+
+    #[test]
+    fn roster_keeps_shared_last_names_apart() {
+        let roster = Roster {
+            employees: vec![
+                Employee {
+                    username: "jdoe".to_string(),
+                    name: "Jonathan Doe".to_string(),
+                    aliases: vec!["Jon Doe".to_string()],
+                },
+                Employee {
+                    username: "sdoe".to_string(),
+                    name: "Sam Doe".to_string(),
+                    aliases: vec![],
+                },
+            ],
+        };
+
+        let mut orders = vec![order("Jon Doe", at(11, 0))];
+        let mut sam = shift("Doe", at(11, 0), 1.0);
+        sam.username = "sdoe".to_string();
+        let mut jon = shift("Doe", at(11, 30), 2.0);
+        jon.username = "jdoe".to_string();
+        let shifts = vec![sam, jon];
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &options(MatchMode::Greedy),
+            &roster,
+            &Overrides::default(),
+        );
+        assert_eq!(result.rows[0].hours, 2.0);
+        assert!(!result.time_sheets[0].matched);
+    }
+
+    #[test]
+    fn orders_match_jobs_inside_one_shift() {
+        let whole = shift("Lee", at(10, 0), 6.0);
+        let span = ShiftSpan {
+            in_time: whole.in_time,
+            out_time: at(16, 0),
+            hours: whole.hours,
+        };
+        let job = |in_time, hours, customer: &str| TimeActivity {
+            customer: customer.to_string(),
+            shift: Some(span),
+            ..shift("Lee", in_time, hours)
+        };
+
+        for mode in [MatchMode::Greedy, MatchMode::Optimal] {
+            let mut orders = vec![order("Ann Lee", at(11, 0)), order("Ann Lee", at(14, 0))];
+
+            // The whole shift can only be paid to one of the orders
+            let result = cross_reference_orders(
+                &mut orders,
+                vec![whole.clone()],
+                &options(mode),
+                &Roster::default(),
+                &Overrides::default(),
+            );
+            assert_eq!(result.matched, 1);
+
+            let jobs = vec![job(at(10, 30), 2.5, "Acme"), job(at(13, 30), 2.5, "Beta")];
+            let result = cross_reference_orders(
+                &mut orders,
+                jobs,
+                &options(mode),
+                &Roster::default(),
+                &Overrides::default(),
+            );
+            assert_eq!(result.matched, 2);
+            assert!(result.rows.iter().all(|row| row.shift == Some(span)));
+            assert_eq!(result.rows[0].suggested_in, Some(at(10, 30)));
+            assert_eq!(result.rows[1].suggested_in, Some(at(13, 30)));
+            assert_eq!(result.rows[1].delta_minutes, Some(-30.0));
+        }
+    }
+
+    #[test]
+    fn rows_record_the_name_rule() {
+        let mut orders = vec![order("Ann Smyth", at(11, 0)), order("Bo Lee", at(15, 0))];
+        let shifts = vec![shift("Lee", at(15, 0), 1.0), shift("Smith", at(11, 0), 2.0)];
+        let mut fuzzy = options(MatchMode::Optimal);
+        fuzzy.fuzzy_threshold = Some(0.7);
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &fuzzy,
+            &Roster::default(),
+            &Overrides::default(),
+        );
+
+        let first = result.rows[0].name_match.unwrap();
+        assert_eq!(first.rule, NameRule::Fuzzy);
+        assert!((first.score - 0.8).abs() < 1e-9);
+        assert_eq!(result.rows[1].name_match.unwrap().rule, NameRule::LastName);
+    }
+
+    #[test]
+    fn optimal_prefers_exact_names_over_nearer_fuzzy_ones() {
+        let mut orders = vec![order("Ann Smith", at(11, 0)), order("Bo Smyth", at(11, 30))];
+        let shifts = vec![shift("Smith", at(11, 25), 2.0)];
+        let mut fuzzy = options(MatchMode::Optimal);
+        fuzzy.fuzzy_threshold = Some(0.7);
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &fuzzy,
+            &Roster::default(),
+            &Overrides::default(),
+        );
+        assert_eq!(result.rows[0].hours, 2.0);
+        assert_eq!(result.rows[0].name_match.unwrap().rule, NameRule::LastName);
+        assert_eq!(result.rows[1].hours, 0.0);
+    }
+
+    #[test]
+    fn rows_explain_their_outcome() {
+        let mut orders = vec![
+            order("Ann Lee", at(11, 0)),
+            order("Ann Lee", at(11, 15)),
+            order("Cy Ward", at(11, 0)),
+            order("Di Penn", at(11, 0)),
+            order("Pickup", at(11, 0)),
+            order("Di Penn", at(15, 0)),
+        ];
+        // No ready time, so the Penn shift at 15:00 stays free
+        orders[5].ready = None;
+        orders[5].datetime = None;
+        let shifts = vec![shift("Lee", at(10, 30), 1.0), shift("Penn", at(15, 0), 1.0)];
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &options(MatchMode::Greedy),
+            &Roster::default(),
+            &Overrides::default(),
+        );
+        let outcomes: Vec<_> = result.rows.iter().map(|row| row.outcome).collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                MatchOutcome::Matched,
+                MatchOutcome::ShiftAlreadyConsumed,
+                MatchOutcome::NoNameCandidate,
+                MatchOutcome::NameButOutsideWindow,
+                MatchOutcome::SkippedInvalidDriver,
+                MatchOutcome::MissingReadyTime,
+            ]
+        );
+        assert!(!result.time_sheets[1].matched);
+
+        assert_eq!(result.rows[0].delta_minutes, Some(-30.0));
+        assert_eq!(result.rows[0].candidate_count, 1);
+        assert_eq!(result.rows[1].candidate_count, 1);
+        assert_eq!(result.rows[2].delta_minutes, None);
+        // Closest Penn shift clocks in four hours after ready
+        assert_eq!(result.rows[3].delta_minutes, Some(240.0));
+        assert_eq!(result.rows[3].candidate_count, 0);
+    }
+
+    #[test]
+    fn overrides_pin_force_and_ignore() {
+        let mut orders = vec![
+            order("Ann Lee", at(11, 0)),
+            order("Ann Lee", at(13, 0)),
+            order("Bo Ward", at(11, 0)),
+        ];
+        let shifts = vec![
+            shift("Lee", at(11, 0), 1.0),
+            shift("Lee", at(16, 0), 2.0),
+            shift("Ward", at(11, 0), 3.0),
+        ];
+
+        let overrides = Overrides {
+            // Far outside the window, but a reviewer says it belongs to the first order
+            pins: vec![Pin {
+                order: OrderKey::of(&orders[0]).unwrap(),
+                shift: ShiftKey::of(&shifts[1]),
+            }],
+            unmatched_orders: vec![],
+            ignored_shifts: vec![ShiftKey::of(&shifts[2])],
+        };
+
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts.clone(),
+            &options(MatchMode::Optimal),
+            &Roster::default(),
+            &overrides,
+        );
+
+        assert_eq!(result.rows[0].outcome, MatchOutcome::Pinned);
+        assert_eq!(result.rows[0].hours, 2.0);
+        assert_eq!(result.rows[0].delta_minutes, Some(300.0));
+        // The freed shift goes to the second order instead
+        assert_eq!(result.rows[1].outcome, MatchOutcome::Matched);
+        assert_eq!(result.rows[1].hours, 1.0);
+        // The only Ward shift is ignored
+        assert_eq!(result.rows[2].outcome, MatchOutcome::ShiftIgnored);
+        assert_eq!(result.rows[2].candidate_count, 0);
+        assert!(result.time_sheets[2].ignored);
+        assert!(!result.time_sheets[2].matched);
+        assert_eq!(result.matched, 2);
+
+        let forced = Overrides {
+            unmatched_orders: vec![OrderKey::of(&orders[2]).unwrap()],
+            ..Default::default()
+        };
+        let result = cross_reference_orders(
+            &mut orders,
+            shifts,
+            &options(MatchMode::Optimal),
+            &Roster::default(),
+            &forced,
+        );
+        assert_eq!(result.rows[2].outcome, MatchOutcome::ForcedUnmatched);
+        assert!(!result.time_sheets[2].matched);
     }
 }
@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::deserialize::Order;
+use crate::deserialize::{Order, ShiftSpan, TimeActivity};
 
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -11,6 +11,112 @@ pub struct PreparedRow {
     pub miles: f64,
     pub suggested_in: Option<DateTime<Utc>>,
     pub suggested_out: Option<DateTime<Utc>>,
+    /// The whole shift the matched job belongs to, in jobs mode
+    pub shift: Option<ShiftSpan>,
+    /// How the driver's name was matched to the shift, if it was
+    pub name_match: Option<NameMatch>,
+    /// Why the row did or didn't get a shift
+    pub outcome: MatchOutcome,
+    /// Clock-in minus ready time in minutes. For misses, the closest shift with a matching name
+    pub delta_minutes: Option<f64>,
+    /// Unignored shifts with a matching name inside the window, taken or not
+    pub candidate_count: usize,
+}
+
+/// Explains the result of matching one order
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchOutcome {
+    Matched,
+    /// No shift in the timesheet carries this driver's name
+    NoNameCandidate,
+    /// The driver has shifts, but none clock in inside the window
+    NameButOutsideWindow,
+    /// Patio parties, pickups and blank drivers are not matched
+    SkippedInvalidDriver,
+    /// The order has no usable date and ready time to match against
+    MissingReadyTime,
+    /// Every shift that fit was already given to another order
+    ShiftAlreadyConsumed,
+    /// The only shifts that fit were marked as intentionally unmatched
+    ShiftIgnored,
+    /// A reviewer pinned this order to a shift
+    Pinned,
+    /// A reviewer marked this order as never matched
+    ForcedUnmatched,
+}
+
+/// Which rule paired a driver's name with a time activity
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum NameRule {
+    /// The employee roster resolved the driver to the activity's username
+    Roster,
+    /// The activity's last name appears in the driver's name
+    LastName,
+    /// The activity's first name matches the driver's first word (expanded orders)
+    FirstName,
+    /// Close but not exact spelling
+    Fuzzy,
+}
+
+impl NameRule {
+    /// Exact rules share the top rank. Optimal matching only takes a lower rank when no
+    /// exact pairing is left
+    pub fn rank(self) -> i64 {
+        match self {
+            NameRule::Roster | NameRule::LastName | NameRule::FirstName => 0,
+            NameRule::Fuzzy => 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameMatch {
+    pub rule: NameRule,
+    /// Similarity from 0 to 1. Exact rules are always 1
+    pub score: f64,
+}
+
+/// How orders are paired with time activities
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MatchMode {
+    /// Each order takes the first unmatched shift that fits, in file order
+    #[default]
+    Greedy,
+    /// Pairs are chosen to minimize the total clock-in distance across all candidates
+    Optimal,
+}
+
+/// How far a clock-in may fall from the order's kitchen ready time
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchWindow {
+    /// Minutes a clock-in may come before the ready time
+    pub before_minutes: i64,
+    /// Minutes a clock-in may come after the ready time
+    pub after_minutes: i64,
+}
+
+impl MatchWindow {
+    /// Symmetric window, as used by the precision slider
+    pub fn from_hours(hours: i64) -> Self {
+        Self {
+            before_minutes: hours * 60,
+            after_minutes: hours * 60,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchOptions {
+    pub window: MatchWindow,
+    pub mode: MatchMode,
+    /// Minimum similarity (0 to 1) for fuzzy name matches. None turns fuzzy matching off
+    pub fuzzy_threshold: Option<f64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,4 +126,6 @@ pub struct ReferenceResult {
     pub matched: u32,
     /// Invalid orders etc
     pub skipped: u32,
+    /// The timesheet as matched, with `matched` set on every used activity
+    pub time_sheets: Vec<TimeActivity>,
 }
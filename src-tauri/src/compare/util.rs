@@ -2,17 +2,24 @@ use std::sync::LazyLock;
 
 use chrono::{DateTime, Duration, Utc};
 
-use crate::deserialize::TimeActivity;
+use crate::{
+    compare::{MatchWindow, NameMatch, NameRule},
+    deserialize::TimeActivity,
+};
 
 static INVALID_NAMES: LazyLock<Vec<String>> =
     LazyLock::new(|| vec!["patio party".to_string(), "pickup".to_string()]);
 
-pub fn is_within_time(time_a: &DateTime<Utc>, time_b: &DateTime<Utc>, precision: i64) -> bool {
-    let duration_limit = Duration::hours(precision);
+/// Whether `clock_in` falls inside the window around the kitchen `ready` time
+pub fn is_within_time(
+    ready: &DateTime<Utc>,
+    clock_in: &DateTime<Utc>,
+    window: &MatchWindow,
+) -> bool {
+    let earliest = *ready - Duration::minutes(window.before_minutes);
+    let latest = *ready + Duration::minutes(window.after_minutes);
 
-    let diff = (*time_a - *time_b).abs();
-
-    diff <= duration_limit
+    earliest <= *clock_in && *clock_in <= latest
 }
 
 pub fn is_name_match(driver: &str, time_activity: &TimeActivity, is_expanded: bool) -> bool {
@@ -29,6 +36,90 @@ pub fn is_name_match(driver: &str, time_activity: &TimeActivity, is_expanded: bo
     }
 }
 
+/// Edit distance between two strings, counted in characters
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Similarity from 0 (nothing alike) to 1 (identical), based on edit distance
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let longest = a.chars().count().max(b.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    1.0 - levenshtein(a, b) as f64 / longest as f64
+}
+
+/// Best similarity between the driver and the same name part `is_name_match` looks at
+pub fn fuzzy_name_score(driver: &str, time_activity: &TimeActivity, is_expanded: bool) -> f64 {
+    if !is_expanded {
+        let time_name = time_activity.last_name.to_lowercase();
+        driver
+            .split_whitespace()
+            .map(|word| similarity(word, &time_name))
+            .fold(0.0, f64::max)
+    } else {
+        let time_name = time_activity.first_name.to_lowercase();
+        driver
+            .split_whitespace()
+            .next()
+            .map_or(0.0, |driver_first| similarity(driver_first, &time_name))
+    }
+}
+
+/// Matches through the roster when it knows the driver, otherwise falls back to the name rules
+/// and finally, when a threshold is given, to fuzzy spelling.
+/// `usernames` is the roster's resolution of `driver`.
+pub fn match_employee(
+    driver: &str,
+    usernames: &[&str],
+    time_activity: &TimeActivity,
+    is_expanded: bool,
+    fuzzy_threshold: Option<f64>,
+) -> Option<NameMatch> {
+    if !usernames.is_empty() {
+        let found = usernames
+            .iter()
+            .any(|username| username.eq_ignore_ascii_case(&time_activity.username));
+
+        return found.then_some(NameMatch {
+            rule: NameRule::Roster,
+            score: 1.0,
+        });
+    }
+
+    if is_name_match(driver, time_activity, is_expanded) {
+        let rule = match is_expanded {
+            true => NameRule::FirstName,
+            false => NameRule::LastName,
+        };
+        return Some(NameMatch { rule, score: 1.0 });
+    }
+
+    let threshold = fuzzy_threshold?;
+    let score = fuzzy_name_score(driver, time_activity, is_expanded);
+
+    (score >= threshold).then_some(NameMatch {
+        rule: NameRule::Fuzzy,
+        score,
+    })
+}
+
 pub fn is_valid_order(name: &str) -> bool {
     if name.trim().is_empty() {
         return false;
@@ -46,18 +137,26 @@ pub fn is_valid_order(name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::deserialize::TimeActivity;
+    use crate::{
+        compare::MatchWindow,
+        deserialize::{RecordSource, TimeActivity},
+    };
     use chrono::{TimeZone, Utc};
 
     fn make_time_activity(first: &str, last: &str) -> TimeActivity {
         TimeActivity {
             first_name: first.to_string(),
             last_name: last.to_string(),
+            username: format!("{}{}", &first[..1], last).to_lowercase(),
             in_time: Utc.with_ymd_and_hms(2023, 1, 1, 10, 0, 0).unwrap(),
             out_time: Utc.with_ymd_and_hms(2023, 1, 1, 11, 0, 0).unwrap(),
             hours: 1.0,
             miles: 0.0,
+            customer: String::new(),
+            shift: None,
             matched: false,
+            ignored: false,
+            source: RecordSource::default(),
         }
     }
 
@@ -66,8 +165,27 @@ mod tests {
         let a = Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
         let b = Utc.with_ymd_and_hms(2023, 1, 1, 10, 0, 0).unwrap();
 
-        assert!(is_within_time(&a, &b, 2));
-        assert!(!is_within_time(&a, &b, 1));
+        assert!(is_within_time(&a, &b, &MatchWindow::from_hours(2)));
+        assert!(!is_within_time(&a, &b, &MatchWindow::from_hours(1)));
+    }
+
+    #[test]
+    fn within_time_asymmetric_minutes() {
+        let ready = Utc.with_ymd_and_hms(2023, 1, 1, 12, 0, 0).unwrap();
+        let window = MatchWindow {
+            before_minutes: 90,
+            after_minutes: 10,
+        };
+
+        let early = Utc.with_ymd_and_hms(2023, 1, 1, 10, 45, 0).unwrap();
+        let slightly_late = Utc.with_ymd_and_hms(2023, 1, 1, 12, 10, 0).unwrap();
+        let too_early = Utc.with_ymd_and_hms(2023, 1, 1, 10, 29, 0).unwrap();
+        let too_late = Utc.with_ymd_and_hms(2023, 1, 1, 12, 11, 0).unwrap();
+
+        assert!(is_within_time(&ready, &early, &window));
+        assert!(is_within_time(&ready, &slightly_late, &window));
+        assert!(!is_within_time(&ready, &too_early, &window));
+        assert!(!is_within_time(&ready, &too_late, &window));
     }
 
     #[test]
@@ -84,6 +202,54 @@ mod tests {
         assert!(is_name_match(driver, &activity, true));
     }
 
+    #[test]
+    fn employee_match_uses_roster_usernames() {
+        let jon = make_time_activity("Jonathan", "Doe");
+        let sam = make_time_activity("Sam", "Doe");
+
+        // Last name alone would match both Does
+        let found = match_employee("jon", &["jdoe"], &jon, false, None).unwrap();
+        assert_eq!(found.rule, NameRule::Roster);
+        assert!(match_employee("jon", &["jdoe"], &sam, false, None).is_none());
+    }
+
+    #[test]
+    fn employee_match_falls_back_without_roster_entry() {
+        let activity = make_time_activity("John", "Doe");
+
+        let found = match_employee("john doe", &[], &activity, false, None).unwrap();
+        assert_eq!(found.rule, NameRule::LastName);
+        assert_eq!(found.score, 1.0);
+        assert!(match_employee("jane roe", &[], &activity, false, None).is_none());
+    }
+
+    #[test]
+    fn levenshtein_distances() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("micheal", "michael"), 2);
+    }
+
+    #[test]
+    fn fuzzy_match_catches_typos_above_threshold() {
+        let activity = make_time_activity("Michael", "Smith");
+
+        // Expanded orders compare first names
+        assert!(match_employee("micheal", &[], &activity, true, None).is_none());
+
+        let found = match_employee("micheal", &[], &activity, true, Some(0.7)).unwrap();
+        assert_eq!(found.rule, NameRule::Fuzzy);
+        assert!((found.score - 5.0 / 7.0).abs() < 1e-9);
+
+        assert!(match_employee("micheal", &[], &activity, true, Some(0.8)).is_none());
+        assert!(match_employee("bob", &[], &activity, true, Some(0.7)).is_none());
+
+        // Non-expanded orders compare last names against every word
+        let found = match_employee("john smtih", &[], &activity, false, Some(0.5)).unwrap();
+        assert_eq!(found.rule, NameRule::Fuzzy);
+    }
+
     #[test]
     fn invalid_order_detection() {
         assert!(!is_valid_order(""));
@@ -1,5 +1,8 @@
 use std::sync::LazyLock;
 
+/// File extensions opened as workbooks, with the format detected by calamine
+pub const WORKBOOK_EXTENSIONS: [&str; 5] = ["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
 pub static CATEREASE_HEADERS: LazyLock<Vec<String>> = LazyLock::new(|| {
     vec![
         "Date".into(),
@@ -27,3 +30,49 @@ pub static INTUIT_HEADERS: LazyLock<Vec<String>> = LazyLock::new(|| {
         "Miles".into(),
     ]
 });
+
+/// Caterease fields an import can't do without. The rest fall back to defaults
+pub static CATEREASE_REQUIRED: LazyLock<Vec<String>> = LazyLock::new(|| {
+    vec![
+        "Date".into(),
+        "Delivery Person".into(),
+        "Client/Organization".into(),
+        "Sub-Event #".into(),
+        "Kitchen Ready by".into(),
+    ]
+});
+
+/// Caterease fields a totals row sums up
+pub const CATEREASE_TOTALS: [&str; 3] = ["Subtotal", "Grat", "Actual"];
+
+/// Intuit fields an import can't do without. The rest fall back to defaults
+pub static INTUIT_REQUIRED: LazyLock<Vec<String>> = LazyLock::new(|| {
+    vec![
+        "First name".into(),
+        "Last name".into(),
+        "Start time".into(),
+        "End time".into(),
+        "Customer".into(),
+    ]
+});
+
+/// Other header names the exports are known to use, keyed by field. Extended by the
+/// `columnSynonyms` setting
+pub static COLUMN_SYNONYMS: LazyLock<Vec<(&str, Vec<&str>)>> = LazyLock::new(|| {
+    vec![
+        ("Delivery Person", vec!["Driver", "Delivery Driver"]),
+        ("Client/Organization", vec!["Client", "Organization"]),
+        ("Sub-Event #", vec!["Sub Event #", "Sub-Event", "Event #"]),
+        (
+            "Kitchen Ready by",
+            vec!["Kitchen Ready", "Ready by", "Ready Time"],
+        ),
+        ("Actual", vec!["Count", "Guest Count"]),
+        ("Grat", vec!["Gratuity"]),
+        ("First name", vec!["First"]),
+        ("Last name", vec!["Last"]),
+        ("Start time", vec!["Clock in", "In time"]),
+        ("End time", vec!["Clock out", "Out time"]),
+        ("Customer", vec!["Activity", "Customer/Activity"]),
+    ]
+});
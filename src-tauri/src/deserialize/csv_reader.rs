@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Context, Result};
+use calamine::{Data, Range};
+
+/// Reads a CSV export into a worksheet-shaped range, so it goes through the same header
+/// mapping and cell parsing as an Excel sheet. Every non-empty value is kept as a string.
+pub fn read_csv_range(file_path: &str) -> Result<Range<Data>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(file_path)
+        .with_context(|| format!("Failed to open CSV file: {}", file_path))?;
+
+    let records = reader
+        .records()
+        .collect::<Result<Vec<_>, _>>()
+        .context("Error reading CSV data")?;
+
+    let width = records.iter().map(|record| record.len()).max().unwrap_or(0);
+    if records.is_empty() || width == 0 {
+        return Err(anyhow!("CSV file is empty"));
+    }
+
+    let mut range = Range::new((0, 0), (records.len() as u32 - 1, width as u32 - 1));
+
+    for (row, record) in records.iter().enumerate() {
+        for (col, value) in record.iter().enumerate() {
+            let value = value.trim();
+            if !value.is_empty() {
+                range.set_value((row as u32, col as u32), Data::String(value.to_string()));
+            }
+        }
+    }
+
+    Ok(range)
+}
@@ -0,0 +1,187 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::deserialize::types::{ImportIssue, Imported, Order, RecordSource, TimeActivity};
+
+/// Records that remember where they were read from
+pub trait Traceable {
+    fn source(&self) -> &RecordSource;
+
+    /// Everything read from the row except where it came from, to spot rows exported twice
+    fn content_key(&self) -> String;
+}
+
+impl Traceable for Order {
+    fn source(&self) -> &RecordSource {
+        &self.source
+    }
+
+    fn content_key(&self) -> String {
+        format!(
+            "{:?}",
+            (
+                self.date,
+                &self.employee,
+                &self.client,
+                &self.description,
+                self.count,
+                self.grat,
+                &self.origin,
+                &self.event,
+                self.ready,
+                self.total,
+            )
+        )
+    }
+}
+
+impl Traceable for TimeActivity {
+    fn source(&self) -> &RecordSource {
+        &self.source
+    }
+
+    fn content_key(&self) -> String {
+        format!(
+            "{:?}",
+            (
+                &self.first_name,
+                &self.last_name,
+                &self.username,
+                self.in_time,
+                self.out_time,
+                self.hours,
+                self.miles,
+                &self.customer,
+            )
+        )
+    }
+}
+
+/// Appends newly imported records to the loaded ones. A record identical to one already
+/// loaded, from any file, is dropped and reported. Returns how many records were added
+pub fn merge_imported<T: Traceable>(
+    records: &mut Vec<T>,
+    issues: &mut Vec<ImportIssue>,
+    imported: Imported<T>,
+) -> usize {
+    let mut seen: HashMap<String, RecordSource> = records
+        .iter()
+        .map(|record| (record.content_key(), record.source().clone()))
+        .collect();
+
+    issues.extend(imported.issues);
+    let before = records.len();
+
+    for record in imported.records {
+        match seen.entry(record.content_key()) {
+            Entry::Occupied(original) => {
+                let source = record.source();
+                issues.push(ImportIssue {
+                    file: source.file.clone(),
+                    sheet: source.sheet.clone(),
+                    row: source.row,
+                    column: String::new(),
+                    value: String::new(),
+                    reason: format!("Duplicate of {}, skipped", original.get().describe()),
+                });
+            }
+            Entry::Vacant(slot) => {
+                slot.insert(record.source().clone());
+                records.push(record);
+            }
+        }
+    }
+
+    records.len() - before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn shift(last: &str, hour: u32, file: &str, row: usize) -> TimeActivity {
+        TimeActivity {
+            first_name: "Ann".to_string(),
+            last_name: last.to_string(),
+            username: last.to_lowercase(),
+            in_time: Utc.with_ymd_and_hms(2025, 3, 3, hour, 0, 0).unwrap(),
+            out_time: Utc.with_ymd_and_hms(2025, 3, 3, hour + 2, 0, 0).unwrap(),
+            hours: 2.0,
+            miles: 0.0,
+            customer: String::new(),
+            shift: None,
+            matched: false,
+            ignored: false,
+            source: RecordSource {
+                file: file.to_string(),
+                sheet: "Timesheets".to_string(),
+                row,
+            },
+        }
+    }
+
+    #[test]
+    fn appends_and_drops_duplicates_across_files() {
+        let mut records = vec![shift("Lee", 18, "crew_a.xlsx", 2)];
+        let mut issues = Vec::new();
+
+        let added = merge_imported(
+            &mut records,
+            &mut issues,
+            Imported {
+                records: vec![
+                    shift("Lee", 18, "crew_b.xlsx", 5),
+                    shift("Ward", 18, "crew_b.xlsx", 6),
+                ],
+                issues: Vec::new(),
+            },
+        );
+
+        assert_eq!(added, 1);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].source.file, "crew_b.xlsx");
+        assert_eq!(
+            issues,
+            vec![ImportIssue {
+                file: "crew_b.xlsx".to_string(),
+                sheet: "Timesheets".to_string(),
+                row: 5,
+                column: String::new(),
+                value: String::new(),
+                reason: "Duplicate of crew_a.xlsx, Timesheets row 2, skipped".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn keeps_import_issues_and_dedupes_within_a_file() {
+        let mut records = Vec::new();
+        let mut issues = Vec::new();
+        let skipped = ImportIssue {
+            file: "times.csv".to_string(),
+            sheet: "times.csv".to_string(),
+            row: 4,
+            column: "Start time".to_string(),
+            value: "yesterday".to_string(),
+            reason: "Not a valid time, row skipped".to_string(),
+        };
+
+        let mut first = shift("Lee", 18, "times.csv", 2);
+        first.source.sheet = "times.csv".to_string();
+        let mut repeat = first.clone();
+        repeat.source.row = 3;
+
+        let added = merge_imported(
+            &mut records,
+            &mut issues,
+            Imported {
+                records: vec![first, repeat],
+                issues: vec![skipped.clone()],
+            },
+        );
+
+        assert_eq!(added, 1);
+        assert_eq!(issues[0], skipped);
+        assert_eq!(issues[1].reason, "Duplicate of times.csv row 2, skipped");
+    }
+}
@@ -1,6 +1,10 @@
+mod csv_reader;
+mod merge;
 mod operations;
+mod row;
 mod types;
 mod util;
 
+pub use merge::*;
 pub use operations::*;
 pub use types::*;
@@ -1,108 +1,946 @@
+use std::path::Path;
+
 use anyhow::{anyhow, Context, Result};
-use calamine::{open_workbook, Reader, Xlsx};
+use calamine::{open_workbook_auto, Data, Range, Reader};
 
 use crate::{
-    constants::{CATEREASE_HEADERS, INTUIT_HEADERS},
+    constants::{
+        CATEREASE_HEADERS, CATEREASE_REQUIRED, CATEREASE_TOTALS, INTUIT_HEADERS, INTUIT_REQUIRED,
+    },
     deserialize::{
-        types::{Order, TimeActivity},
+        csv_reader::read_csv_range,
+        row::RowReader,
+        types::{
+            ImportIssue, ImportOptions, Imported, IntuitMode, Order, Role, ShiftSpan, SourceFormat,
+            TimeActivity,
+        },
         util::{
-            deserialize_date_cell, deserialize_float_cell, deserialize_int_cell,
-            deserialize_string_cell, deserialize_string_date, join_date_and_time,
+            detect_format, is_blank_cell, join_date_and_time, parse_float_cell, serial_to_date,
+            serial_to_time,
         },
     },
-    validate::validate_headers,
+    drivers::parse_drivers,
+    validate::map_headers,
 };
 
-pub fn deserialize_caterease_excel(file_path: &str) -> Result<Vec<Order>> {
-    let mut workbook: Xlsx<_> = open_workbook(file_path)
-        .with_context(|| format!("Failed to open Excel file: {}", file_path))?;
+/// Reads a Caterease export, picking the reader from the file's extension or contents
+pub fn deserialize_caterease(file_path: &str, options: &ImportOptions) -> Result<Imported<Order>> {
+    match detect_format(file_path)? {
+        SourceFormat::Workbook => deserialize_caterease_workbook(file_path, options),
+        SourceFormat::Csv => deserialize_caterease_csv(file_path, options),
+    }
+}
+
+/// Reads a QuickBooks Time export, picking the reader from the file's extension or contents
+pub fn deserialize_intuit(
+    file_path: &str,
+    options: &ImportOptions,
+) -> Result<Imported<TimeActivity>> {
+    match detect_format(file_path)? {
+        SourceFormat::Workbook => deserialize_intuit_workbook(file_path, options),
+        SourceFormat::Csv => deserialize_intuit_csv(file_path, options),
+    }
+}
 
-    let worksheet = workbook
-        .worksheet_range_at(0)
-        .context("Couldn't find first worksheet")?
-        .context("Error reading worksheet data")?;
+/// Reads every sheet of an .xlsx, .xls or .ods workbook that holds orders, such as one sheet
+/// per week. A sheet that can't be read is reported with its error and empty sheets are
+/// passed over. When no sheet holds orders, the first sheet's error is returned
+pub fn deserialize_caterease_workbook(
+    file_path: &str,
+    options: &ImportOptions,
+) -> Result<Imported<Order>> {
+    let mut workbook = open_workbook_auto(file_path)
+        .with_context(|| format!("Failed to open workbook: {}", file_path))?;
 
-    validate_headers(&worksheet, &CATEREASE_HEADERS)?;
+    let file = file_name(file_path);
+    let mut parts = Vec::new();
+    let mut skipped = Vec::new();
+    let mut first_error = None;
 
-    // There must be at least one header, one order, and one sum row
-    if worksheet.height() < 3 {
-        return Err(anyhow!("Not enough orders"));
+    for sheet in workbook.sheet_names() {
+        let worksheet = workbook
+            .worksheet_range(&sheet)
+            .context("Error reading worksheet data")?;
+
+        if worksheet.is_empty() {
+            continue;
+        }
+
+        match parse_orders(&worksheet, &file, &sheet, options) {
+            Ok(part) => parts.push(part),
+            Err(e) => {
+                skipped.push(ImportIssue {
+                    file: file.clone(),
+                    sheet: sheet.clone(),
+                    row: 1,
+                    column: String::new(),
+                    value: String::new(),
+                    reason: format!("{}, sheet not imported", e),
+                });
+                first_error.get_or_insert(e);
+            }
+        }
     }
 
+    if parts.is_empty() {
+        return Err(first_error.unwrap_or_else(|| anyhow!("Couldn't find first worksheet")));
+    }
+
+    let mut imported = Imported::concat(parts);
+    imported.issues.extend(skipped);
+
+    Ok(imported)
+}
+
+pub fn deserialize_caterease_csv(
+    file_path: &str,
+    options: &ImportOptions,
+) -> Result<Imported<Order>> {
+    let worksheet = read_csv_range(file_path)?;
+    let file = file_name(file_path);
+
+    parse_orders(&worksheet, &file, &file, options)
+}
+
+fn parse_orders(
+    worksheet: &Range<Data>,
+    file: &str,
+    sheet: &str,
+    options: &ImportOptions,
+) -> Result<Imported<Order>> {
+    let columns = map_headers(
+        worksheet,
+        &CATEREASE_HEADERS,
+        &CATEREASE_REQUIRED,
+        &options.synonyms,
+    )?;
+
+    let first_row = worksheet.start().map_or(0, |(row, _)| row as usize);
     let mut orders = Vec::new();
+    let mut issues = Vec::new();
 
-    for row in worksheet.rows().skip(1).take(worksheet.height() - 2) {
-        let datetime = match join_date_and_time(row.first(), row.get(8)) {
-            Some(dt) => dt,
-            None => continue,
+    for (index, row) in worksheet.rows().enumerate().skip(1) {
+        if row.iter().all(|cell| is_blank_cell(Some(cell))) {
+            continue;
+        }
+
+        let mut reader = RowReader {
+            file,
+            sheet,
+            number: first_row + index + 1,
+            row,
+            columns: &columns,
+            issues: &mut issues,
         };
 
+        // Every order has a sub-event, so anything without one or a driver is a totals row
+        // or other trailing content
+        if reader.string("Delivery Person").trim().is_empty()
+            && reader.string("Sub-Event #").trim().is_empty()
+        {
+            match totals_field(&reader) {
+                Some(field) => reader.report(field, "Totals row, not imported"),
+                None => reader.report("Sub-Event #", "No driver or sub-event, row skipped"),
+            }
+            continue;
+        }
+
+        let date = reader.date("Date").and_then(serial_to_date);
+        let ready = reader.date("Kitchen Ready by").and_then(serial_to_time);
+
+        let resolved = join_date_and_time(date, ready, options.timezone, options.dst_policy);
+        let datetime = resolved.and_then(|resolved| resolved.utc);
+
+        if let Some(warning) = resolved.and_then(|resolved| resolved.warning(options.dst_policy)) {
+            match datetime {
+                Some(_) => reader.report("Kitchen Ready by", &warning),
+                None => reader.report(
+                    "Kitchen Ready by",
+                    &format!("{}, order can't be matched", warning),
+                ),
+            }
+        }
+
+        // Text that isn't a name is left out when the order is split per driver
+        let employee = reader.string("Delivery Person");
+        let notes = parse_drivers(&employee).notes;
+        if !notes.is_empty() {
+            reader.report(
+                "Delivery Person",
+                &format!("Read as a note, not a name: {}", notes.join("; ")),
+            );
+        }
+
         let order = Order {
-            date: deserialize_date_cell(row.first(), 45658.0),
-            employee: deserialize_string_cell(row.get(1), ""),
-            client: deserialize_string_cell(row.get(2), ""),
-            description: deserialize_string_cell(row.get(3), ""),
-            count: deserialize_int_cell(row.get(4), 0),
-            grat: deserialize_float_cell(row.get(5), 0.0),
-            origin: deserialize_string_cell(row.get(6), ""),
-            event: deserialize_string_cell(row.get(7), ""),
-            ready: deserialize_date_cell(row.get(8), 0.0),
-            total: deserialize_float_cell(row.get(9), 0.0),
+            date,
+            employee,
+            client: reader.string("Client/Organization"),
+            description: reader.string("Description"),
+            count: reader.int("Actual", 0),
+            grat: reader.float("Grat", 0.0),
+            origin: reader.string("Delivery Category"),
+            event: reader.string("Sub-Event #"),
+            ready,
+            total: reader.float("Subtotal", 0.0),
             datetime,
             expanded: false,
+            role: Role::Lead,
+            source: reader.source(),
         };
 
         orders.push(order);
     }
 
-    Ok(orders)
+    if orders.is_empty() {
+        return Err(anyhow!("Not enough orders"));
+    }
+
+    Ok(Imported {
+        records: orders,
+        issues,
+    })
 }
 
-pub fn deserialize_intuit_excel(file_path: &str) -> Result<Vec<TimeActivity>> {
-    let mut workbook: Xlsx<_> = open_workbook(file_path)
-        .with_context(|| format!("Failed to open Excel file: {}", file_path))?;
+/// First total field holding a number
+fn totals_field(reader: &RowReader) -> Option<&'static str> {
+    CATEREASE_TOTALS
+        .iter()
+        .copied()
+        .find(|field| reader.cell(field).and_then(parse_float_cell).is_some())
+}
 
-    let worksheet = workbook
-        .worksheet_range("Timesheets")
-        .context("Cannot find sheet named 'Timesheets'")?;
+/// Reads every Timesheets sheet of an .xlsx, .xls or .ods workbook. Exports split by crew
+/// add sheets such as "Timesheets (2)"
+pub fn deserialize_intuit_workbook(
+    file_path: &str,
+    options: &ImportOptions,
+) -> Result<Imported<TimeActivity>> {
+    let mut workbook = open_workbook_auto(file_path)
+        .with_context(|| format!("Failed to open workbook: {}", file_path))?;
 
-    validate_headers(&worksheet, &INTUIT_HEADERS)?;
+    let sheets: Vec<String> = workbook
+        .sheet_names()
+        .into_iter()
+        .filter(|sheet| sheet.to_lowercase().starts_with("timesheets"))
+        .collect();
+    if sheets.is_empty() {
+        return Err(anyhow!("Cannot find sheet named 'Timesheets'"));
+    }
 
-    let mut time_sheets: Vec<TimeActivity> = Vec::new();
+    let file = file_name(file_path);
+    let mut parts = Vec::new();
 
-    for row in worksheet.rows().skip(1) {
-        let activity_type = deserialize_string_cell(row.get(5), "");
-        if activity_type != "Shift Total" {
-            continue;
-        }
+    for sheet in sheets {
+        let worksheet = workbook
+            .worksheet_range(&sheet)
+            .with_context(|| format!("Error reading sheet '{}'", sheet))?;
+
+        parts.push(parse_time_activities(&worksheet, &file, &sheet, options)?);
+    }
+
+    Ok(Imported::concat(parts))
+}
+
+pub fn deserialize_intuit_csv(
+    file_path: &str,
+    options: &ImportOptions,
+) -> Result<Imported<TimeActivity>> {
+    let worksheet = read_csv_range(file_path)?;
+    let file = file_name(file_path);
 
-        let in_result = deserialize_string_date(row.get(3));
-        let in_time = match in_result {
-            Ok(dt) => dt,
-            Err(_) => continue,
+    parse_time_activities(&worksheet, &file, &file, options)
+}
+
+fn parse_time_activities(
+    worksheet: &Range<Data>,
+    file: &str,
+    sheet: &str,
+    options: &ImportOptions,
+) -> Result<Imported<TimeActivity>> {
+    let columns = map_headers(
+        worksheet,
+        &INTUIT_HEADERS,
+        &INTUIT_REQUIRED,
+        &options.synonyms,
+    )?;
+
+    let first_row = worksheet.start().map_or(0, |(row, _)| row as usize);
+    let mut shifts: Vec<TimeActivity> = Vec::new();
+    let mut jobs: Vec<TimeActivity> = Vec::new();
+    let mut issues = Vec::new();
+
+    for (index, row) in worksheet.rows().enumerate().skip(1) {
+        let mut reader = RowReader {
+            file,
+            sheet,
+            number: first_row + index + 1,
+            row,
+            columns: &columns,
+            issues: &mut issues,
         };
 
-        let out_result = deserialize_string_date(row.get(4));
-        let out_time = match out_result {
-            Ok(dt) => dt,
-            Err(_) => continue,
+        let customer = reader.string("Customer");
+        let is_shift = customer == "Shift Total";
+
+        match options.intuit_mode {
+            IntuitMode::Shifts if !is_shift => continue,
+            IntuitMode::Jobs if customer.trim().is_empty() => continue,
+            _ => {}
+        }
+
+        let Some(in_time) = reader.timestamp("Start time", options) else {
+            continue;
         };
 
-        let first_name = deserialize_string_cell(row.first(), "");
-        let last_name = deserialize_string_cell(row.get(1), "");
+        let Some(out_time) = reader.timestamp("End time", options) else {
+            continue;
+        };
 
         let activity = TimeActivity {
-            first_name,
-            last_name,
+            first_name: reader.string("First name"),
+            last_name: reader.string("Last name"),
+            username: reader.string("Username"),
             in_time,
             out_time,
-            hours: deserialize_float_cell(row.get(6), 0.0),
-            miles: deserialize_float_cell(row.get(7), 0.0),
+            hours: reader.float("Hours", 0.0),
+            miles: reader.float("Miles", 0.0),
+            customer: if is_shift { String::new() } else { customer },
+            shift: None,
             matched: false,
+            ignored: false,
+            source: reader.source(),
         };
 
-        time_sheets.push(activity);
+        if is_shift {
+            shifts.push(activity);
+        } else {
+            jobs.push(activity);
+        }
+    }
+
+    let time_sheets = group_jobs(shifts, jobs, &mut issues);
+
+    Ok(Imported {
+        records: time_sheets,
+        issues,
+    })
+}
+
+/// Replaces each shift with the jobs it covers, in file order. Shifts without jobs are kept
+/// whole, and jobs outside every shift are kept on their own and reported
+fn group_jobs(
+    shifts: Vec<TimeActivity>,
+    jobs: Vec<TimeActivity>,
+    issues: &mut Vec<ImportIssue>,
+) -> Vec<TimeActivity> {
+    let mut grouped: Vec<Vec<TimeActivity>> = vec![Vec::new(); shifts.len()];
+    let mut loose = Vec::new();
+
+    for mut job in jobs {
+        let parent = shifts.iter().position(|shift| {
+            shift.same_employee(&job)
+                && shift.in_time <= job.in_time
+                && job.out_time <= shift.out_time
+        });
+
+        match parent {
+            Some(index) => {
+                let shift = &shifts[index];
+                job.shift = Some(ShiftSpan {
+                    in_time: shift.in_time,
+                    out_time: shift.out_time,
+                    hours: shift.hours,
+                });
+                grouped[index].push(job);
+            }
+            None => {
+                issues.push(ImportIssue {
+                    file: job.source.file.clone(),
+                    sheet: job.source.sheet.clone(),
+                    row: job.source.row,
+                    column: "Customer".to_string(),
+                    value: job.customer.clone(),
+                    reason: "Job isn't inside any shift total, kept on its own".to_string(),
+                });
+                loose.push(job);
+            }
+        }
+    }
+
+    shifts
+        .into_iter()
+        .zip(grouped)
+        .flat_map(|(shift, jobs)| if jobs.is_empty() { vec![shift] } else { jobs })
+        .chain(loose)
+        .collect()
+}
+
+/// File name without its folder. CSV files have no sheets, so it names the sheet too
+fn file_name(file_path: &str) -> String {
+    Path::new(file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        deserialize::ImportIssue, settings::ColumnSynonyms, test_util::temp_dir,
+        timezone::DstPolicy,
+    };
+    use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+    use std::fs;
+
+    fn pacific() -> ImportOptions {
+        ImportOptions {
+            synonyms: ColumnSynonyms::new(),
+            timezone: chrono_tz::America::Los_Angeles,
+            dst_policy: DstPolicy::Earliest,
+            intuit_mode: IntuitMode::Shifts,
+        }
     }
 
-    Ok(time_sheets)
+    fn write_temp(name: &str, contents: &str) -> String {
+        let path = temp_dir().join(name);
+        fs::write(&path, contents).unwrap();
+        path.to_string_lossy().to_string()
+    }
+
+    fn fixture(name: &str) -> String {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(name)
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn reads_caterease_workbooks_in_every_format() {
+        for extension in ["xlsx", "xls", "ods"] {
+            let path = fixture(&format!("caterease.{}", extension));
+            let orders = deserialize_caterease(&path, &pacific()).unwrap().records;
+
+            assert_eq!(orders.len(), 2, "{}", extension);
+            assert_eq!(
+                orders[0].date,
+                NaiveDate::from_ymd_opt(2025, 3, 3),
+                "{}",
+                extension
+            );
+            assert_eq!(
+                orders[0].ready,
+                NaiveTime::from_hms_opt(11, 30, 0),
+                "{}",
+                extension
+            );
+            assert_eq!(orders[0].employee, "Ann Lee", "{}", extension);
+            assert_eq!(orders[0].count, 20, "{}", extension);
+            assert_eq!(orders[0].grat, 15.5, "{}", extension);
+            assert_eq!(orders[1].event, "13", "{}", extension);
+            assert_eq!(
+                orders[0].datetime,
+                Some(Utc.with_ymd_and_hms(2025, 3, 3, 19, 30, 0).unwrap()),
+                "{}",
+                extension
+            );
+            assert_eq!(
+                orders[1].datetime,
+                Some(Utc.with_ymd_and_hms(2025, 3, 5, 0, 0, 0).unwrap()),
+                "{}",
+                extension
+            );
+        }
+    }
+
+    #[test]
+    fn reads_intuit_workbooks_in_every_format() {
+        for extension in ["xlsx", "xls", "ods"] {
+            let path = fixture(&format!("intuit.{}", extension));
+            let time_sheets = deserialize_intuit(&path, &pacific()).unwrap().records;
+
+            assert_eq!(time_sheets.len(), 2, "{}", extension);
+            assert_eq!(time_sheets[0].username, "alee", "{}", extension);
+            assert_eq!(
+                time_sheets[1].in_time,
+                Utc.with_ymd_and_hms(2025, 3, 4, 23, 50, 0).unwrap(),
+                "{}",
+                extension
+            );
+            assert_eq!(time_sheets[1].hours, 2.17, "{}", extension);
+        }
+    }
+
+    #[test]
+    fn workbooks_are_detected_from_content() {
+        let dir = temp_dir();
+        for extension in ["xlsx", "xls", "ods"] {
+            let renamed = dir.join(format!("intuit_{}.export", extension));
+            fs::copy(fixture(&format!("intuit.{}", extension)), &renamed).unwrap();
+            let renamed = renamed.to_string_lossy().to_string();
+
+            assert_eq!(detect_format(&renamed).unwrap(), SourceFormat::Workbook);
+            assert_eq!(
+                deserialize_intuit(&renamed, &pacific())
+                    .unwrap()
+                    .records
+                    .len(),
+                2
+            );
+            let _ = fs::remove_file(&renamed);
+        }
+    }
+
+    #[test]
+    fn reads_caterease_csv() {
+        let path = write_temp(
+            "caterease_test.csv",
+            "Date,Delivery Person,Client/Organization,Description,Actual,Grat,Delivery Category,Sub-Event #,Kitchen Ready by,Subtotal\n\
+             3/3/2025,Ann Lee,Acme,Lunch,25,12.50,Delivery,12,11:30 AM,300.00\n\
+             ,,,,,,,,,300.00\n",
+        );
+
+        let orders = deserialize_caterease(&path, &pacific()).unwrap().records;
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(orders.len(), 1);
+        let order = &orders[0];
+        assert_eq!(order.date, NaiveDate::from_ymd_opt(2025, 3, 3));
+        assert_eq!(order.employee, "Ann Lee");
+        assert_eq!(order.event, "12");
+        assert_eq!(order.count, 25);
+        assert_eq!(order.grat, 12.5);
+        assert_eq!(
+            order.datetime,
+            Some(Utc.with_ymd_and_hms(2025, 3, 3, 19, 30, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn reads_two_digit_years_in_csv() {
+        let path = write_temp(
+            "caterease_short_year.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by\n\
+             3/9/25,Ann Lee,Acme,12,11:30 AM\n\
+             3/10/2025,Ann Lee,Acme,13,11:30 AM\n\
+             ,,,,\n",
+        );
+        let intuit = write_temp(
+            "intuit_short_year.csv",
+            "First name,Last name,Username,Start time,End time,Customer,Hours,Miles\n\
+             Ann,Lee,alee,3/9/25 11:00 AM,3/9/25 2:00 PM,Shift Total,3.0,0\n",
+        );
+
+        let orders = deserialize_caterease(&path, &pacific()).unwrap().records;
+        let time_sheets = deserialize_intuit(&intuit, &pacific()).unwrap().records;
+        let _ = fs::remove_file(&path);
+        let _ = fs::remove_file(&intuit);
+
+        assert_eq!(orders[0].date, NaiveDate::from_ymd_opt(2025, 3, 9));
+        assert_eq!(orders[1].date, NaiveDate::from_ymd_opt(2025, 3, 10));
+        assert_eq!(
+            time_sheets[0].in_time,
+            Utc.with_ymd_and_hms(2025, 3, 9, 18, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn reads_intuit_csv() {
+        let path = write_temp(
+            "intuit_test.csv",
+            "First name,Last name,Username,Start time,End time,Customer,Hours,Miles\n\
+             Ann,Lee,alee,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total,3.0,12.5\n\
+             Ann,Lee,alee,2025-03-03 11:00:00,2025-03-03 14:00:00,Acme,3.0,12.5\n",
+        );
+
+        let time_sheets = deserialize_intuit(&path, &pacific()).unwrap().records;
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(time_sheets.len(), 1);
+        assert_eq!(time_sheets[0].username, "alee");
+        assert_eq!(
+            time_sheets[0].in_time,
+            Utc.with_ymd_and_hms(2025, 3, 3, 19, 0, 0).unwrap()
+        );
+        assert_eq!(time_sheets[0].miles, 12.5);
+    }
+
+    #[test]
+    fn times_are_read_in_the_configured_timezone() {
+        let path = write_temp(
+            "intuit_tz_test.csv",
+            "First name,Last name,Start time,End time,Customer\n\
+             Ann,Lee,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total\n\
+             Bob,Jones,2025-11-02 01:30:00,2025-11-02 03:00:00,Shift Total\n",
+        );
+        let eastern = ImportOptions {
+            timezone: chrono_tz::America::New_York,
+            ..pacific()
+        };
+
+        let in_eastern = deserialize_intuit(&path, &eastern).unwrap();
+        let in_pacific = deserialize_intuit(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(
+            in_eastern.records[0].in_time,
+            Utc.with_ymd_and_hms(2025, 3, 3, 16, 0, 0).unwrap()
+        );
+        // 1:30 AM repeats when DST ends, the shift is kept rather than dropped
+        assert_eq!(
+            in_pacific.records[1].in_time,
+            Utc.with_ymd_and_hms(2025, 11, 2, 8, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn dst_transitions_are_reported() {
+        let shifts = write_temp(
+            "intuit_dst_test.csv",
+            "First name,Last name,Start time,End time,Customer\n\
+             Ann,Lee,2025-03-09 02:30:00,2025-03-09 05:00:00,Shift Total\n\
+             Bob,Jones,2025-11-02 01:30:00,2025-11-02 03:00:00,Shift Total\n",
+        );
+        let orders = write_temp(
+            "caterease_dst_test.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by\n\
+             3/9/2025,Ann Lee,Acme,12,2:30 AM\n\
+             11/2/2025,Bob Jones,Acme,13,1:30 AM\n\
+             ,,,,\n",
+        );
+        let latest = ImportOptions {
+            dst_policy: DstPolicy::Latest,
+            ..pacific()
+        };
+        let flag = ImportOptions {
+            dst_policy: DstPolicy::Flag,
+            ..pacific()
+        };
+
+        let latest_shifts = deserialize_intuit(&shifts, &latest).unwrap();
+        let flagged_shifts = deserialize_intuit(&shifts, &flag).unwrap();
+        let flagged_orders = deserialize_caterease(&orders, &flag).unwrap();
+        let _ = fs::remove_file(&shifts);
+        let _ = fs::remove_file(&orders);
+
+        assert_eq!(latest_shifts.records.len(), 2);
+        assert_eq!(
+            latest_shifts.records[0].in_time,
+            Utc.with_ymd_and_hms(2025, 3, 9, 10, 30, 0).unwrap()
+        );
+        assert_eq!(
+            latest_shifts.records[1].in_time,
+            Utc.with_ymd_and_hms(2025, 11, 2, 9, 30, 0).unwrap()
+        );
+        let reasons: Vec<&str> = latest_shifts
+            .issues
+            .iter()
+            .map(|issue| issue.reason.as_str())
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "Time is skipped when DST starts, read as the later instant",
+                "Time happens twice when DST ends, read as the later instant",
+            ]
+        );
+
+        assert!(flagged_shifts.records.is_empty());
+        assert_eq!(
+            flagged_shifts.issues[0].reason,
+            "Time is skipped when DST starts, needs review, row skipped"
+        );
+
+        // Flagged orders are kept so they show up as unmatched
+        assert_eq!(flagged_orders.records.len(), 2);
+        assert!(flagged_orders
+            .records
+            .iter()
+            .all(|order| order.datetime.is_none()));
+        assert_eq!(
+            flagged_orders.issues[1].reason,
+            "Time happens twice when DST ends, needs review, order can't be matched"
+        );
+    }
+
+    #[test]
+    fn jobs_are_grouped_under_their_shift() {
+        let path = write_temp(
+            "intuit_jobs_test.csv",
+            "First name,Last name,Username,Start time,End time,Customer,Hours\n\
+             Ann,Lee,alee,2025-03-03 10:00:00,2025-03-03 12:30:00,Acme,2.5\n\
+             Ann,Lee,alee,2025-03-03 13:00:00,2025-03-03 16:00:00,Beta,3.0\n\
+             Ann,Lee,alee,2025-03-03 10:00:00,2025-03-03 16:00:00,Shift Total,6.0\n\
+             Bob,Jones,bjones,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total,3.0\n\
+             Cy,Ward,cward,2025-03-03 09:00:00,2025-03-03 10:00:00,Gamma,1.0\n",
+        );
+        let jobs = ImportOptions {
+            intuit_mode: IntuitMode::Jobs,
+            ..pacific()
+        };
+
+        let by_shift = deserialize_intuit(&path, &pacific()).unwrap();
+        let by_job = deserialize_intuit(&path, &jobs).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(by_shift.records.len(), 2);
+        assert!(by_shift.issues.is_empty());
+
+        let customers: Vec<&str> = by_job
+            .records
+            .iter()
+            .map(|activity| activity.customer.as_str())
+            .collect();
+        assert_eq!(customers, vec!["Acme", "Beta", "", "Gamma"]);
+
+        let span = by_job.records[1].shift.unwrap();
+        assert_eq!(
+            span.in_time,
+            Utc.with_ymd_and_hms(2025, 3, 3, 18, 0, 0).unwrap()
+        );
+        assert_eq!(span.hours, 6.0);
+        assert_eq!(by_job.records[1].hours, 3.0);
+        // Bob's shift has no jobs, so it's kept whole
+        assert!(by_job.records[2].shift.is_none());
+
+        assert_eq!(
+            by_job.issues,
+            vec![ImportIssue {
+                file: "intuit_jobs_test.csv".to_string(),
+                sheet: "intuit_jobs_test.csv".to_string(),
+                row: 6,
+                column: "Customer".to_string(),
+                value: "Gamma".to_string(),
+                reason: "Job isn't inside any shift total, kept on its own".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn csv_is_detected_from_content() {
+        let path = write_temp(
+            "intuit_test.txt",
+            "First name,Last name,Start time,End time,Customer\n\
+             Ann,Lee,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total\n",
+        );
+
+        assert_eq!(detect_format(&path).unwrap(), SourceFormat::Csv);
+        assert_eq!(
+            deserialize_intuit(&path, &pacific()).unwrap().records.len(),
+            1
+        );
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn totals_rows_are_found_by_content() {
+        let path = write_temp(
+            "caterease_totals.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by,Subtotal\n\
+             3/3/2025,Ann Lee,Acme,12,11:30 AM,300.00\n\
+             ,,Week 1,,,300.00\n\
+             3/10/2025,,Beta,13,11:30 AM,150.00\n\
+             3/10/2025,Bob Jones,Gamma,14,4:00 PM,\n\
+             ,,Grand total,,,\"$1,250.00\"\n\
+             ,,,,,\n\
+             ,,Printed 3/11/2025,,,\n",
+        );
+        let orders = deserialize_caterease(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        // A blank driver with a sub-event is still an order, and the last order has no total
+        let events: Vec<&str> = orders
+            .records
+            .iter()
+            .map(|order| order.event.as_str())
+            .collect();
+        assert_eq!(events, vec!["12", "13", "14"]);
+
+        let dropped: Vec<(usize, &str, &str)> = orders
+            .issues
+            .iter()
+            .map(|issue| (issue.row, issue.column.as_str(), issue.reason.as_str()))
+            .collect();
+        assert_eq!(
+            dropped,
+            vec![
+                (3, "Subtotal", "Totals row, not imported"),
+                (5, "Subtotal", "Blank, defaulted to 0"),
+                (6, "Subtotal", "Totals row, not imported"),
+                (8, "Sub-Event #", "No driver or sub-event, row skipped"),
+            ]
+        );
+
+        let path = write_temp(
+            "caterease_only_totals.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by,Subtotal\n\
+             ,,,,,0\n",
+        );
+        assert!(deserialize_caterease(&path, &pacific()).is_err());
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn sheets_that_fail_are_reported() {
+        let path = temp_dir().join("caterease_weeks.xlsx");
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+
+        let headers = [
+            "Date",
+            "Delivery Person",
+            "Client/Organization",
+            "Sub-Event #",
+        ];
+        let week_1 = workbook.add_worksheet().set_name("Week 1").unwrap();
+        week_1.write_row(0, 0, headers).unwrap();
+        week_1.write(0, 4, "Kitchen Ready by").unwrap();
+        week_1
+            .write_row(1, 0, ["3/3/2025", "Ann Lee", "Acme", "12", "11:30 AM"])
+            .unwrap();
+        // Someone renamed a header on the second week
+        let week_2 = workbook.add_worksheet().set_name("Week 2").unwrap();
+        week_2.write_row(0, 0, headers).unwrap();
+        week_2.write(0, 4, "Ready").unwrap();
+        week_2
+            .write_row(1, 0, ["3/10/2025", "Ann Lee", "Acme", "13", "11:30 AM"])
+            .unwrap();
+        workbook.add_worksheet().set_name("Notes").unwrap();
+        workbook.save(&path).unwrap();
+
+        let orders = deserialize_caterease(&path.to_string_lossy(), &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(orders.records.len(), 1);
+        assert_eq!(
+            orders.issues,
+            vec![ImportIssue {
+                file: "caterease_weeks.xlsx".to_string(),
+                sheet: "Week 2".to_string(),
+                row: 1,
+                column: String::new(),
+                value: String::new(),
+                reason: "Missing required columns in file: Kitchen Ready by, sheet not imported"
+                    .to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn blank_numbers_are_reported() {
+        let path = write_temp(
+            "intuit_blank_numbers.csv",
+            "First name,Last name,Start time,End time,Customer,Hours,Miles\n\
+             Ann,Lee,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total,3.0,\n\
+             Bob,Jones,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total, ,4\n",
+        );
+        let time_sheets = deserialize_intuit(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(time_sheets.records.len(), 2);
+        assert_eq!(time_sheets.records[1].hours, 0.0);
+
+        let defaulted: Vec<(usize, &str, &str)> = time_sheets
+            .issues
+            .iter()
+            .map(|issue| (issue.row, issue.column.as_str(), issue.reason.as_str()))
+            .collect();
+        assert_eq!(
+            defaulted,
+            vec![
+                (2, "Miles", "Blank, defaulted to 0"),
+                (3, "Hours", "Blank, defaulted to 0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn driver_notes_are_reported() {
+        let path = write_temp(
+            "caterease_notes.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by\n\
+             3/3/2025,Ann - Bo,Acme,12,11:30 AM\n\
+             3/3/2025,Cy (Di),Beta,13,11:30 AM\n",
+        );
+        let orders = deserialize_caterease(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(orders.records[0].employee, "Ann - Bo");
+        assert_eq!(
+            orders.issues,
+            vec![ImportIssue {
+                file: "caterease_notes.csv".to_string(),
+                sheet: "caterease_notes.csv".to_string(),
+                row: 2,
+                column: "Delivery Person".to_string(),
+                value: "Ann - Bo".to_string(),
+                reason: "Read as a note, not a name: Bo".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn skipped_and_defaulted_cells_are_reported() {
+        let path = write_temp(
+            "caterease_issues.csv",
+            "Date,Delivery Person,Client/Organization,Sub-Event #,Kitchen Ready by,Actual\n\
+             3/3/2025,Ann Lee,Acme,12,11:30 AM,lots\n\
+             3/3/2025,Bob Jones,Beta,13,,20\n\
+             ,,,,,45\n",
+        );
+        let orders = deserialize_caterease(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(orders.records.len(), 2);
+        assert_eq!(orders.records[0].count, 0);
+        assert_eq!(
+            orders.issues,
+            vec![
+                ImportIssue {
+                    file: "caterease_issues.csv".to_string(),
+                    sheet: "caterease_issues.csv".to_string(),
+                    row: 2,
+                    column: "Actual".to_string(),
+                    value: "lots".to_string(),
+                    reason: "Not a whole number, defaulted to 0".to_string(),
+                },
+                ImportIssue {
+                    file: "caterease_issues.csv".to_string(),
+                    sheet: "caterease_issues.csv".to_string(),
+                    row: 3,
+                    column: "Kitchen Ready by".to_string(),
+                    value: "".to_string(),
+                    reason: "Missing value".to_string(),
+                },
+                ImportIssue {
+                    file: "caterease_issues.csv".to_string(),
+                    sheet: "caterease_issues.csv".to_string(),
+                    row: 4,
+                    column: "Actual".to_string(),
+                    value: "45".to_string(),
+                    reason: "Totals row, not imported".to_string(),
+                },
+            ]
+        );
+
+        let path = write_temp(
+            "intuit_issues.csv",
+            "First name,Last name,Start time,End time,Customer\n\
+             Ann,Lee,yesterday,2025-03-03 14:00:00,Shift Total\n\
+             Ann,Lee,2025-03-03 11:00:00,2025-03-03 14:00:00,Acme\n\
+             Bob,Jones,2025-03-03 11:00:00,2025-03-03 14:00:00,Shift Total\n",
+        );
+        let time_sheets = deserialize_intuit(&path, &pacific()).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(time_sheets.records.len(), 1);
+        assert_eq!(time_sheets.issues.len(), 1);
+        assert_eq!(time_sheets.issues[0].row, 2);
+        assert_eq!(time_sheets.issues[0].column, "Start time");
+        assert_eq!(time_sheets.issues[0].value, "yesterday");
+        assert_eq!(
+            time_sheets.issues[0].reason,
+            "Not a valid time, row skipped"
+        );
+    }
 }
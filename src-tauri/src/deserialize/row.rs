@@ -0,0 +1,141 @@
+use calamine::Data;
+use chrono::{DateTime, Utc};
+
+use crate::{
+    deserialize::{
+        types::{ImportIssue, ImportOptions, RecordSource},
+        util::{
+            deserialize_string_date, is_blank_cell, parse_date_cell, parse_float_cell,
+            parse_int_cell,
+        },
+    },
+    validate::ColumnMap,
+};
+
+/// Reads the fields of one row, noting every cell that has to be skipped or defaulted
+pub struct RowReader<'a> {
+    pub file: &'a str,
+    pub sheet: &'a str,
+    /// Row number as shown in the spreadsheet
+    pub number: usize,
+    pub row: &'a [Data],
+    pub columns: &'a ColumnMap,
+    pub issues: &'a mut Vec<ImportIssue>,
+}
+
+impl<'a> RowReader<'a> {
+    pub fn cell(&self, field: &str) -> Option<&'a Data> {
+        self.columns.cell(self.row, field)
+    }
+
+    pub fn source(&self) -> RecordSource {
+        RecordSource {
+            file: self.file.to_string(),
+            sheet: self.sheet.to_string(),
+            row: self.number,
+        }
+    }
+
+    pub fn report(&mut self, field: &str, reason: &str) {
+        let value = self
+            .cell(field)
+            .map(|cell| cell.to_string())
+            .unwrap_or_default();
+
+        self.issues.push(ImportIssue {
+            file: self.file.to_string(),
+            sheet: self.sheet.to_string(),
+            row: self.number,
+            column: field.to_string(),
+            value,
+            reason: reason.to_string(),
+        });
+    }
+
+    pub fn string(&self, field: &str) -> String {
+        match self.cell(field) {
+            Some(Data::String(value)) => value.to_string(),
+            Some(Data::Empty) | None => String::new(),
+            Some(other) => other.to_string(),
+        }
+    }
+
+    /// Date or time as an Excel serial. None, reported, when the cell is blank or holds
+    /// something else
+    pub fn date(&mut self, field: &str) -> Option<f64> {
+        if is_blank_cell(self.cell(field)) {
+            self.report(field, "Missing value");
+            return None;
+        }
+
+        let serial = self.cell(field).and_then(parse_date_cell);
+        if serial.is_none() {
+            self.report(field, "Not a date or time");
+        }
+
+        serial
+    }
+
+    /// Local timestamp as UTC. None, reported, when it can't be parsed or falls in a DST
+    /// transition the policy leaves for review
+    pub fn timestamp(&mut self, field: &str, options: &ImportOptions) -> Option<DateTime<Utc>> {
+        let Ok(resolved) =
+            deserialize_string_date(self.cell(field), options.timezone, options.dst_policy)
+        else {
+            self.report(field, "Not a valid time, row skipped");
+            return None;
+        };
+
+        if let Some(warning) = resolved.warning(options.dst_policy) {
+            match resolved.utc {
+                Some(_) => self.report(field, &warning),
+                None => self.report(field, &format!("{}, row skipped", warning)),
+            }
+        }
+
+        resolved.utc
+    }
+
+    /// Notes a blank cell that falls back to `default`. A field the file has no column for
+    /// isn't noted on every row, since the header check already allowed it
+    fn report_blank(&mut self, field: &str, default: impl std::fmt::Display) {
+        if self.columns.has(field) {
+            self.report(field, &format!("Blank, defaulted to {}", default));
+        }
+    }
+
+    /// Whole number. A blank or unreadable cell counts as the default and is reported
+    pub fn int(&mut self, field: &str, default: i64) -> i64 {
+        if is_blank_cell(self.cell(field)) {
+            self.report_blank(field, default);
+            return default;
+        }
+
+        match self.cell(field).and_then(parse_int_cell) {
+            Some(value) => value,
+            None => {
+                self.report(
+                    field,
+                    &format!("Not a whole number, defaulted to {}", default),
+                );
+                default
+            }
+        }
+    }
+
+    /// Number. A blank or unreadable cell counts as the default and is reported
+    pub fn float(&mut self, field: &str, default: f64) -> f64 {
+        if is_blank_cell(self.cell(field)) {
+            self.report_blank(field, default);
+            return default;
+        }
+
+        match self.cell(field).and_then(parse_float_cell) {
+            Some(value) => value,
+            None => {
+                self.report(field, &format!("Not a number, defaulted to {}", default));
+                default
+            }
+        }
+    }
+}
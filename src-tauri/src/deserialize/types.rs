@@ -1,9 +1,13 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 
+use crate::{settings::ColumnSynonyms, timezone::DstPolicy};
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Order {
-    pub date: f64,
+    // None when the Date cell is blank or not a date
+    pub date: Option<NaiveDate>,
     pub employee: String,
     pub client: String,
     pub description: String,
@@ -11,22 +15,169 @@ pub struct Order {
     pub grat: f64,
     pub origin: String,
     pub event: String,
-    pub ready: f64,
+    // Kitchen ready time. None when the cell is blank or not a time
+    pub ready: Option<NaiveTime>,
     pub total: f64,
-    // Joined date/time of the event
-    pub datetime: DateTime<Utc>,
+    // Order date joined with the kitchen ready time. None when either is missing, so the
+    // order can't be matched by time
+    pub datetime: Option<DateTime<Utc>>,
     // Order has been expanded for having multiple drivers
     pub expanded: bool,
+    // The driver's part in the delivery, from the Delivery Person cell
+    pub role: Role,
+    // Where the order was read from
+    pub source: RecordSource,
 }
 
 #[derive(Clone, Debug, Serialize)]
 pub struct TimeActivity {
     pub first_name: String,
     pub last_name: String,
+    // Intuit username, the stable key into the employee roster
+    pub username: String,
     pub in_time: DateTime<Utc>,
     pub out_time: DateTime<Utc>,
     pub hours: f64,
     pub miles: f64,
+    // Customer of a per-job row, empty for a shift total
+    pub customer: String,
+    // The shift total a per-job row was grouped under
+    pub shift: Option<ShiftSpan>,
     // This has already been matched with an event
     pub matched: bool,
+    // A reviewer marked this as intentionally unmatched
+    pub ignored: bool,
+    // Where the activity was read from
+    pub source: RecordSource,
+}
+
+impl TimeActivity {
+    /// Whether both activities belong to one employee. Usernames are compared when both rows
+    /// have one, names otherwise
+    pub(crate) fn same_employee(&self, other: &TimeActivity) -> bool {
+        if !self.username.is_empty() && !other.username.is_empty() {
+            return self.username.eq_ignore_ascii_case(&other.username);
+        }
+
+        self.first_name.eq_ignore_ascii_case(&other.first_name)
+            && self.last_name.eq_ignore_ascii_case(&other.last_name)
+    }
+}
+
+/// Someone's part in a delivery
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Role {
+    /// The only driver named outside parentheses
+    #[default]
+    Lead,
+    /// One of several drivers named outside parentheses
+    CoDriver,
+    /// Named in parentheses, riding along with the driver
+    Helper,
+}
+
+/// Where a record was read from, so merged inputs can be traced back
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordSource {
+    /// File name, without its folder
+    pub file: String,
+    /// Worksheet name, or the file name for CSV exports
+    pub sheet: String,
+    /// Row number as shown in the spreadsheet
+    pub row: usize,
+}
+
+impl RecordSource {
+    /// Short reference such as "orders.xlsx, Sheet1 row 4"
+    pub fn describe(&self) -> String {
+        if self.sheet == self.file {
+            format!("{} row {}", self.file, self.row)
+        } else {
+            format!("{}, {} row {}", self.file, self.sheet, self.row)
+        }
+    }
+}
+
+/// Clock-in and clock-out of a whole shift, kept on each of its per-job rows
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ShiftSpan {
+    pub in_time: DateTime<Utc>,
+    pub out_time: DateTime<Utc>,
+    pub hours: f64,
+}
+
+/// Which Intuit rows become time activities
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IntuitMode {
+    /// One activity per shift, from the "Shift Total" rows
+    #[default]
+    Shifts,
+    /// One activity per job inside a shift, so each delivery can be matched on its own.
+    /// Shifts without job rows are kept whole
+    Jobs,
+}
+
+/// File formats the exports can be read from
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SourceFormat {
+    /// .xlsx, .xls or .ods, opened with format detection
+    Workbook,
+    Csv,
+}
+
+/// A cell that was skipped or replaced with a default while importing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportIssue {
+    /// File name, without its folder
+    pub file: String,
+    /// Worksheet name, or the file name for CSV exports
+    pub sheet: String,
+    /// Row number as shown in the spreadsheet, starting at 1 for the header
+    pub row: usize,
+    /// Field the cell belongs to
+    pub column: String,
+    /// Cell contents as read
+    pub value: String,
+    pub reason: String,
+}
+
+/// Records read from an export, with every problem found along the way
+#[derive(Clone, Debug)]
+pub struct Imported<T> {
+    pub records: Vec<T>,
+    pub issues: Vec<ImportIssue>,
+}
+
+impl<T> Imported<T> {
+    /// Joins what was read from several sheets, in order
+    pub fn concat(parts: Vec<Imported<T>>) -> Self {
+        let mut joined = Self {
+            records: Vec::new(),
+            issues: Vec::new(),
+        };
+
+        for part in parts {
+            joined.records.extend(part.records);
+            joined.issues.extend(part.issues);
+        }
+
+        joined
+    }
+}
+
+/// How an export's cells are interpreted
+#[derive(Clone, Debug)]
+pub struct ImportOptions {
+    /// Extra header names for each field
+    pub synonyms: ColumnSynonyms,
+    /// Timezone the export's local times are in
+    pub timezone: Tz,
+    /// How times in a DST transition are read
+    pub dst_policy: DstPolicy,
+    /// Whether Intuit shifts are split into their jobs
+    pub intuit_mode: IntuitMode,
 }
@@ -1,91 +1,200 @@
-use anyhow::{anyhow, Result};
-use calamine::{Data, DataType, ExcelDateTime, ExcelDateTimeType};
-use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
-use chrono_tz::US::Pacific;
-
-pub fn deserialize_string_cell(cell: Option<&Data>, default: &str) -> String {
-    cell.and_then(|cell| cell.get_string())
-        .unwrap_or(default)
-        .to_string()
+use std::{fs::File, io::Read, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use calamine::{Data, DataType};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+use chrono_tz::Tz;
+
+use crate::{
+    constants::WORKBOOK_EXTENSIONS,
+    deserialize::types::SourceFormat,
+    timezone::{resolve_local, DstPolicy, Resolved},
+};
+
+/// Date formats accepted from text cells, as CSV exports write them. Two-digit years come
+/// first, since `%Y` would read "3/9/25" as the year 25
+const DATE_FORMATS: [&str; 3] = ["%m/%d/%y", "%m/%d/%Y", "%Y-%m-%d"];
+
+/// Time of day formats accepted from text cells
+const TIME_FORMATS: [&str; 4] = ["%I:%M %p", "%I:%M:%S %p", "%H:%M", "%H:%M:%S"];
+
+/// Timestamp formats accepted for Intuit start and end times, two-digit years first
+const DATETIME_FORMATS: [&str; 6] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%m/%d/%y %I:%M %p",
+    "%m/%d/%Y %I:%M %p",
+    "%m/%d/%y %H:%M",
+    "%m/%d/%Y %H:%M",
+];
+
+/// ISO durations LibreOffice writes for times of day
+const DURATION_FORMATS: [&str; 2] = ["PT%HH%MM%SS", "PT%HH%MM%S%.fS"];
+
+/// Picks a reader from the file extension, falling back to the contents. .xlsx and .ods
+/// files are zip archives and .xls files are OLE compound files, anything else is read as CSV.
+pub fn detect_format(file_path: &str) -> Result<SourceFormat> {
+    let extension = Path::new(file_path)
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase());
+
+    match extension.as_deref() {
+        Some("csv") => return Ok(SourceFormat::Csv),
+        Some(ext) if WORKBOOK_EXTENSIONS.contains(&ext) => return Ok(SourceFormat::Workbook),
+        _ => {}
+    }
+
+    let mut magic = [0u8; 4];
+    let read = File::open(file_path)
+        .and_then(|mut file| file.read(&mut magic))
+        .with_context(|| format!("Failed to open file: {}", file_path))?;
+
+    if read == 4 && (magic == *b"PK\x03\x04" || magic == [0xD0, 0xCF, 0x11, 0xE0]) {
+        Ok(SourceFormat::Workbook)
+    } else {
+        Ok(SourceFormat::Csv)
+    }
 }
 
-pub fn deserialize_date_cell(cell: Option<&Data>, default: f64) -> f64 {
-    let excel_dt = cell
-        .and_then(|cell| {
-            if let Data::DateTime(excel_datetime) = cell {
-                Some(*excel_datetime)
-            } else {
-                None
-            }
-        })
-        .unwrap_or(ExcelDateTime::new(
-            default,
-            ExcelDateTimeType::DateTime,
-            false,
-        ));
+/// Excel serial for a date or time of day written as text
+fn parse_text_serial(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?;
 
-    excel_dt.as_f64()
+    if let Ok(serial) = value.parse::<f64>() {
+        return Some(serial);
+    }
+
+    if let Some(date) = DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(value, format).ok())
+    {
+        return Some((date - excel_epoch).num_days() as f64);
+    }
+
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+        .map(|time| time.num_seconds_from_midnight() as f64 / 86_400.0)
 }
 
-pub fn deserialize_int_cell(cell: Option<&Data>, default: i64) -> i64 {
-    cell.map(|x| match x {
-        Data::Int(x) => *x,
-        Data::String(x) => x.parse::<i64>().unwrap_or(default),
-        Data::Float(x) => *x as i64,
-        _ => 0,
-    })
-    .unwrap()
+/// True for a missing cell, an empty cell, or one holding only whitespace
+pub fn is_blank_cell(cell: Option<&Data>) -> bool {
+    match cell {
+        None | Some(Data::Empty) => true,
+        Some(Data::String(value)) => value.trim().is_empty(),
+        _ => false,
+    }
 }
 
-pub fn deserialize_float_cell(cell: Option<&Data>, default: f64) -> f64 {
-    cell.map(|x| match x {
-        Data::Float(x) => *x,
-        Data::String(x) => x.parse::<f64>().unwrap_or(default),
-        Data::Int(x) => *x as f64,
-        _ => 0.0,
-    })
-    .unwrap()
+/// Excel serial of a date or time cell, None when the cell holds something else. Text cells
+/// from CSV exports are parsed
+pub fn parse_date_cell(cell: &Data) -> Option<f64> {
+    match cell {
+        Data::DateTime(excel_datetime) => Some(excel_datetime.as_f64()),
+        Data::Float(serial) => Some(*serial),
+        Data::Int(serial) => Some(*serial as f64),
+        Data::String(value) => parse_text_serial(value),
+        Data::DateTimeIso(value) => parse_iso_serial(value),
+        Data::DurationIso(value) => DURATION_FORMATS
+            .iter()
+            .find_map(|format| NaiveTime::parse_from_str(value, format).ok())
+            .map(|time| time.num_seconds_from_midnight() as f64 / 86_400.0),
+        _ => None,
+    }
 }
 
-pub fn deserialize_string_date(cell: Option<&Data>) -> Result<DateTime<Utc>> {
-    let string_date = cell.and_then(|c| c.get_string()).unwrap_or("");
+/// Whole number in a cell, None when the cell holds something else
+pub fn parse_int_cell(cell: &Data) -> Option<i64> {
+    match cell {
+        Data::Int(x) => Some(*x),
+        Data::Float(x) => Some(*x as i64),
+        Data::String(x) => x.trim().parse::<i64>().ok(),
+        _ => None,
+    }
+}
 
-    let parsed = NaiveDateTime::parse_from_str(string_date, "%Y-%m-%d %H:%M:%S")?;
+/// Number in a cell, None when the cell holds something else. Currency text such as
+/// "$1,250.00" is accepted
+pub fn parse_float_cell(cell: &Data) -> Option<f64> {
+    match cell {
+        Data::Float(x) => Some(*x),
+        Data::Int(x) => Some(*x as f64),
+        Data::String(x) => x.trim().replace(['$', ','], "").parse::<f64>().ok(),
+        _ => None,
+    }
+}
 
-    let pacific_time = parsed
-        .and_local_timezone(Pacific)
-        .single()
-        .ok_or_else(|| anyhow!("Couldn't convert time"))?;
+/// Excel serial for an ISO date or date and time, as .ods files store them
+fn parse_iso_serial(value: &str) -> Option<f64> {
+    let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_time(NaiveTime::MIN);
 
-    let to_utc = pacific_time.with_timezone(&Utc);
+    let datetime = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|d| d.and_time(NaiveTime::MIN))
+        })
+        .ok()?;
 
-    Ok(to_utc)
+    Some((datetime - excel_epoch).num_seconds() as f64 / 86_400.0)
 }
 
-pub fn join_date_and_time(
-    date_cell: Option<&Data>,
-    time_cell: Option<&Data>,
-) -> Option<DateTime<Utc>> {
-    // Initial values
-    let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?;
-    let date_val = deserialize_date_cell(date_cell, 45658.0);
-    let time_val = deserialize_date_cell(time_cell, 0.0);
+/// Local date and time for an Excel serial
+fn excel_serial_to_naive(serial: f64) -> Option<NaiveDateTime> {
+    let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?.and_time(NaiveTime::MIN);
+    let seconds = (serial * 86_400.0).round() as i64;
 
-    // Date
-    let days = date_val.floor() as i64;
-    let date = excel_epoch.checked_add_signed(Duration::days(days))?;
+    excel_epoch.checked_add_signed(Duration::seconds(seconds))
+}
 
-    // Time
-    let total_seconds = (time_val * 24.0 * 60.0 * 60.0).round() as u32;
-    let hours = total_seconds / 3600;
-    let minutes = (total_seconds % 3600) / 60;
-    let seconds = total_seconds % 60;
-    let time = NaiveTime::from_hms_opt(hours, minutes, seconds)?;
+/// Reads a timestamp cell, written as text or saved as a real date by a spreadsheet app, as
+/// local time in `timezone`
+pub fn deserialize_string_date(
+    cell: Option<&Data>,
+    timezone: Tz,
+    policy: DstPolicy,
+) -> Result<Resolved> {
+    let parsed = match cell {
+        Some(Data::DateTime(excel_datetime)) => excel_serial_to_naive(excel_datetime.as_f64()),
+        Some(Data::DateTimeIso(value)) => parse_iso_serial(value).and_then(excel_serial_to_naive),
+        _ => None,
+    };
+
+    let parsed = match parsed {
+        Some(parsed) => parsed,
+        None => {
+            let string_date = cell.and_then(|c| c.get_string()).unwrap_or("");
+
+            DATETIME_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(string_date, format).ok())
+                .ok_or_else(|| anyhow!("Couldn't parse time: '{}'", string_date))?
+        }
+    };
+
+    Ok(resolve_local(parsed, timezone, policy))
+}
+
+/// Calendar date of an Excel serial, ignoring any time of day
+pub fn serial_to_date(serial: f64) -> Option<NaiveDate> {
+    let excel_epoch = NaiveDate::from_ymd_opt(1899, 12, 30)?;
 
-    let naive_datetime = date.and_time(time);
+    excel_epoch.checked_add_signed(Duration::days(serial.floor() as i64))
+}
 
-    let pacific_dt = naive_datetime.and_local_timezone(Pacific).single()?;
-    let utc_dt = pacific_dt.to_utc();
+/// Time of day of an Excel serial, ignoring any date
+pub fn serial_to_time(serial: f64) -> Option<NaiveTime> {
+    let seconds = ((serial - serial.floor()) * 86_400.0).round() as u32;
 
-    Some(utc_dt)
+    // Rounding can land on the next midnight
+    NaiveTime::from_num_seconds_from_midnight_opt(seconds % 86_400, 0)
+}
+
+/// Order date and local ready time as a UTC instant. None when either is missing
+pub fn join_date_and_time(
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    timezone: Tz,
+    policy: DstPolicy,
+) -> Option<Resolved> {
+    Some(resolve_local(date?.and_time(time?), timezone, policy))
 }
@@ -0,0 +1,477 @@
+/// Words that describe someone's part in a delivery rather than name them, as in
+/// "(helper: Bo)" or "Ann (Bo training)". Only read as labels where a label can go, since
+/// they are surnames too
+const ROLE_WORDS: [&str; 9] = [
+    "helper", "helpers", "helping", "driver", "drivers", "driving", "lead", "trainee", "training",
+];
+
+/// Names that stand for nobody, as in "(no helper)"
+const NOBODY: [&str; 4] = ["no", "none", "nobody", "tbd"];
+
+/// Characters that end a name and separate it from the next one
+const SEPARATORS: [char; 6] = [',', ';', '&', '+', '/', '\n'];
+
+/// People named in a Delivery Person cell
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Drivers {
+    /// Drivers named outside parentheses, in order
+    pub leads: Vec<String>,
+    /// People named in parentheses or after "with", in order
+    pub helpers: Vec<String>,
+    /// Text that was read as a note rather than a name, so it can be reported
+    pub notes: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    /// "and", a comma and the like
+    Separator,
+    Open,
+    Close,
+    /// "with" or "w/". Whoever follows is helping
+    With,
+    /// A dash or "#". Everything after it, up to the next name, is a note
+    Note,
+    /// "*", a note unless it opens a name, as in "*Ann*"
+    Star,
+    /// Text in brackets, always a note
+    Bracketed(String),
+    /// Starts a note after a name, but follows a label like "helper:"
+    Colon,
+}
+
+/// Ends the word being read, classifying it
+fn end_word(word: &mut String, tokens: &mut Vec<Token>) {
+    if word.is_empty() {
+        return;
+    }
+
+    let is_dash = |c: char| matches!(c, '-' | '–' | '—');
+
+    if word.starts_with(is_dash) {
+        tokens.push(Token::Note);
+    } else {
+        let trimmed = word.trim_end_matches(is_dash);
+
+        tokens.push(match trimmed.to_lowercase().as_str() {
+            "and" => Token::Separator,
+            "with" => Token::With,
+            _ => Token::Word(trimmed.to_string()),
+        });
+
+        if trimmed.len() < word.len() {
+            tokens.push(Token::Note);
+        }
+    }
+
+    word.clear();
+}
+
+fn tokenize(field: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut chars = field.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '/' && word.eq_ignore_ascii_case("w") {
+            word.clear();
+            tokens.push(Token::With);
+            continue;
+        }
+
+        if !(c.is_whitespace() || SEPARATORS.contains(&c) || "()[]*#:".contains(c)) {
+            word.push(c);
+            continue;
+        }
+
+        end_word(&mut word, &mut tokens);
+
+        match c {
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '[' => tokens.push(Token::Bracketed(
+                chars.by_ref().take_while(|c| *c != ']').collect(),
+            )),
+            '*' => tokens.push(Token::Star),
+            '#' => tokens.push(Token::Note),
+            ':' => tokens.push(Token::Colon),
+            c if SEPARATORS.contains(&c) => tokens.push(Token::Separator),
+            _ => {}
+        }
+    }
+
+    end_word(&mut word, &mut tokens);
+
+    tokens
+}
+
+/// Reads tokens into names, tracking whether each was named as a helper
+struct Parser {
+    drivers: Drivers,
+    name: Vec<String>,
+    /// One entry per open parenthesis, plus the top level. True while naming helpers
+    helping: Vec<bool>,
+}
+
+fn is_role_word(word: &str) -> bool {
+    ROLE_WORDS.contains(&word.to_lowercase().as_str())
+}
+
+impl Parser {
+    fn flush(&mut self) {
+        let helping = self.helping.last() == Some(&true);
+
+        // A role word labels the name when it stands alone or, among helpers, opens or
+        // closes it. Elsewhere it's part of the name, as in "Adam Driver"
+        if self.name.len() == 1 || helping {
+            if self.name.first().is_some_and(|word| is_role_word(word)) {
+                self.name.remove(0);
+            }
+            if self.name.last().is_some_and(|word| is_role_word(word)) {
+                self.name.pop();
+            }
+        }
+
+        if self.name.is_empty() {
+            return;
+        }
+
+        let name = self.name.join(" ");
+        self.name.clear();
+
+        if NOBODY.contains(&name.to_lowercase().as_str()) {
+            return;
+        }
+
+        match self.helping.last() {
+            Some(true) => self.drivers.helpers.push(name),
+            _ => self.drivers.leads.push(name),
+        }
+    }
+
+    fn open(&mut self) {
+        self.flush();
+        self.helping.push(true);
+    }
+
+    fn close(&mut self) {
+        self.flush();
+        if self.helping.len() > 1 {
+            self.helping.pop();
+        }
+    }
+
+    fn note(&mut self, text: &str) {
+        let text = text.trim();
+        if !text.is_empty() {
+            self.drivers.notes.push(text.to_string());
+        }
+    }
+
+    /// Drops a role word written as a label before a colon, as in "helper: Sam". True when
+    /// nothing else was named, so the colon only ended the label
+    fn take_label(&mut self) -> bool {
+        if self.name.last().is_some_and(|word| is_role_word(word)) {
+            self.name.pop();
+        }
+
+        self.name.is_empty()
+    }
+
+    fn parse(mut self, tokens: Vec<Token>) -> Drivers {
+        let mut tokens = tokens.into_iter();
+
+        while let Some(token) = tokens.next() {
+            match token {
+                Token::Word(word) => self.name.push(word),
+                Token::Separator => self.flush(),
+                Token::Open => self.open(),
+                Token::Close => self.close(),
+                Token::With => {
+                    self.flush();
+                    if let Some(helping) = self.helping.last_mut() {
+                        *helping = true;
+                    }
+                }
+                Token::Bracketed(text) => self.note(&text),
+                Token::Star if self.name.is_empty() => {}
+                Token::Colon if self.take_label() => {}
+                Token::Note | Token::Star | Token::Colon => {
+                    self.flush();
+
+                    // The note runs to the end of its parentheses or, outside them, to the
+                    // next separator
+                    let top_level = self.helping.len() == 1;
+                    let mut note = Vec::new();
+                    let mut depth = 0;
+                    for skipped in tokens.by_ref() {
+                        match skipped {
+                            Token::Open => depth += 1,
+                            Token::Close if depth == 0 => {
+                                self.close();
+                                break;
+                            }
+                            Token::Close => depth -= 1,
+                            Token::Separator if depth == 0 && top_level => break,
+                            Token::Word(word) | Token::Bracketed(word) => note.push(word),
+                            _ => {}
+                        }
+                    }
+                    self.note(&note.join(" "));
+                }
+            }
+        }
+
+        self.flush();
+
+        self.drivers
+    }
+}
+
+/// Drops repeated names, keeping the first. Someone named as both a lead and a helper is
+/// kept as a lead
+fn dedupe(drivers: Drivers) -> Drivers {
+    let mut seen: Vec<String> = Vec::new();
+    let mut keep = |names: Vec<String>| -> Vec<String> {
+        names
+            .into_iter()
+            .filter(|name| {
+                let key = name.to_lowercase();
+                let new = !seen.contains(&key);
+                seen.push(key);
+                new
+            })
+            .collect()
+    };
+
+    let leads = keep(drivers.leads);
+    let helpers = keep(drivers.helpers);
+
+    Drivers {
+        leads,
+        helpers,
+        notes: drivers.notes,
+    }
+}
+
+/// Reads the Delivery Person cell into lead drivers and helpers. Names are split on
+/// "and", "&", "/", "+", commas and semicolons; names in parentheses, at any depth, or
+/// after "with" are helpers; and anything in brackets or after a dash, colon, "*" or "#",
+/// up to the next name or closing parenthesis, is a note. Helpers named without a lead are
+/// read as leads
+pub fn parse_drivers(field: &str) -> Drivers {
+    // Patio parties are booked under the event, not a person
+    if field.to_lowercase().contains("patio party") {
+        return Drivers {
+            leads: vec![field.to_string()],
+            ..Default::default()
+        };
+    }
+
+    let parser = Parser {
+        drivers: Drivers::default(),
+        name: Vec::new(),
+        helping: vec![false],
+    };
+
+    let mut drivers = dedupe(parser.parse(tokenize(field)));
+
+    if drivers.leads.is_empty() {
+        drivers.leads = std::mem::take(&mut drivers.helpers);
+    }
+
+    drivers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Delivery Person cells with the leads and helpers they name
+    const CASES: &[(&str, &[&str], &[&str])] = &[
+        // One driver
+        ("Ann Lee", &["Ann Lee"], &[]),
+        ("  Ann   Lee  ", &["Ann Lee"], &[]),
+        ("Ann", &["Ann"], &[]),
+        ("Mary-Jane O'Neil", &["Mary-Jane O'Neil"], &[]),
+        ("J. R. Smith Jr.", &["J. R. Smith Jr."], &[]),
+        ("José Núñez", &["José Núñez"], &[]),
+        // "and" only counts as a whole word
+        ("Alexander", &["Alexander"], &[]),
+        ("Sandra Lee", &["Sandra Lee"], &[]),
+        ("Fernando Diaz", &["Fernando Diaz"], &[]),
+        ("Andy Anderson", &["Andy Anderson"], &[]),
+        ("Rolando Candela", &["Rolando Candela"], &[]),
+        ("Brandon and Amanda", &["Brandon", "Amanda"], &[]),
+        ("Alexander AND Sandra", &["Alexander", "Sandra"], &[]),
+        // Co-drivers
+        ("Ann, Bo", &["Ann", "Bo"], &[]),
+        ("Ann,Bo", &["Ann", "Bo"], &[]),
+        ("Ann & Bo", &["Ann", "Bo"], &[]),
+        ("Ann&Bo", &["Ann", "Bo"], &[]),
+        ("Ann / Bo", &["Ann", "Bo"], &[]),
+        ("Ann/Bo", &["Ann", "Bo"], &[]),
+        ("Ann + Bo", &["Ann", "Bo"], &[]),
+        ("Ann; Bo", &["Ann", "Bo"], &[]),
+        ("Ann\nBo", &["Ann", "Bo"], &[]),
+        (
+            "Ann Lee, Bo Diaz and Cy Ward",
+            &["Ann Lee", "Bo Diaz", "Cy Ward"],
+            &[],
+        ),
+        ("Ann, Bo, & Cy", &["Ann", "Bo", "Cy"], &[]),
+        ("Ann, and Bo", &["Ann", "Bo"], &[]),
+        ("Ann Lee / Bo Diaz + Cy", &["Ann Lee", "Bo Diaz", "Cy"], &[]),
+        // Helpers in parentheses
+        ("John (Sam)", &["John"], &["Sam"]),
+        ("John(Sam)", &["John"], &["Sam"]),
+        ("John Doe (Sam Roe)", &["John Doe"], &["Sam Roe"]),
+        ("John (Sam, Kim)", &["John"], &["Sam", "Kim"]),
+        ("John (Sam and Kim)", &["John"], &["Sam", "Kim"]),
+        ("John (Sam & Kim / Lu)", &["John"], &["Sam", "Kim", "Lu"]),
+        ("John (Sam) (Kim)", &["John"], &["Sam", "Kim"]),
+        ("John (Sam), Ann (Kim)", &["John", "Ann"], &["Sam", "Kim"]),
+        ("John and Ann (Sam)", &["John", "Ann"], &["Sam"]),
+        ("(Sam) John", &["John"], &["Sam"]),
+        ("John (Sam (Kim))", &["John"], &["Sam", "Kim"]),
+        ("John ((Sam))", &["John"], &["Sam"]),
+        ("John (Sam", &["John"], &["Sam"]),
+        ("John Sam)", &["John Sam"], &[]),
+        ("John ()", &["John"], &[]),
+        // Labelled helpers
+        ("John (helper: Sam)", &["John"], &["Sam"]),
+        ("John (Helper Sam)", &["John"], &["Sam"]),
+        ("John (Sam helping)", &["John"], &["Sam"]),
+        ("John (Sam training)", &["John"], &["Sam"]),
+        ("Driver: John", &["John"], &[]),
+        ("Lead: John (trainee Sam)", &["John"], &["Sam"]),
+        ("John (Helper)", &["John"], &[]),
+        ("John, Driver", &["John"], &[]),
+        // Role words that are surnames
+        ("Adam Driver", &["Adam Driver"], &[]),
+        ("Lisa Lead", &["Lisa Lead"], &[]),
+        (
+            "Adam Driver and Lisa Lead",
+            &["Adam Driver", "Lisa Lead"],
+            &[],
+        ),
+        ("Lisa Lead (Sam)", &["Lisa Lead"], &["Sam"]),
+        ("Adam Driver (helper: Sam)", &["Adam Driver"], &["Sam"]),
+        ("Bo Helper w/ Sam", &["Bo Helper"], &["Sam"]),
+        ("John with Sam", &["John"], &["Sam"]),
+        ("John w/ Sam", &["John"], &["Sam"]),
+        ("John w/Sam and Kim", &["John"], &["Sam", "Kim"]),
+        ("John W/ Sam", &["John"], &["Sam"]),
+        // Nobody
+        ("John (no helper)", &["John"], &[]),
+        ("John (none)", &["John"], &[]),
+        ("John, TBD", &["John"], &[]),
+        ("", &[], &[]),
+        ("   ", &[], &[]),
+        ("(none)", &[], &[]),
+        // Trailing notes
+        ("John - 2 trips", &["John"], &[]),
+        ("John -- call ahead", &["John"], &[]),
+        ("John – early", &["John"], &[]),
+        ("John — early", &["John"], &[]),
+        ("John -early", &["John"], &[]),
+        ("John- early", &["John"], &[]),
+        ("John: leaves at 10", &["John"], &[]),
+        ("John *call client*", &["John"], &[]),
+        ("John # van 2", &["John"], &[]),
+        ("John #2", &["John"], &[]),
+        ("John [van 2]", &["John"], &[]),
+        ("John [van 2] and Sam", &["John", "Sam"], &[]),
+        ("John (Sam) - setup only", &["John"], &["Sam"]),
+        (
+            "John (Sam - first week) and Ann",
+            &["John", "Ann"],
+            &["Sam"],
+        ),
+        (
+            "John (Sam: 2nd stop (late)) and Ann",
+            &["John", "Ann"],
+            &["Sam"],
+        ),
+        ("John - see (notes)", &["John"], &[]),
+        // Notes end at the next name
+        ("John - 2 trips, Ann", &["John", "Ann"], &[]),
+        ("John # van 2 & Ann", &["John", "Ann"], &[]),
+        ("*Ann* and Bo", &["Ann", "Bo"], &[]),
+        ("*Ann*", &["Ann"], &[]),
+        ("Ann - Bo", &["Ann"], &[]),
+        // Repeats
+        ("John and John", &["John"], &[]),
+        ("John (john)", &["John"], &[]),
+        ("Sam (John) and John", &["Sam", "John"], &[]),
+        // Helpers with nobody leading drove it themselves
+        ("(Sam)", &["Sam"], &[]),
+        ("(Sam, Kim)", &["Sam", "Kim"], &[]),
+        ("w/ Sam", &["Sam"], &[]),
+        // Full names as booked, with made-up people
+        ("Maria Gonzalez", &["Maria Gonzalez"], &[]),
+        ("MARIA GONZALEZ", &["MARIA GONZALEZ"], &[]),
+        (
+            "Maria Gonzalez (Kevin Tran)",
+            &["Maria Gonzalez"],
+            &["Kevin Tran"],
+        ),
+        (
+            "Maria Gonzalez & Kevin Tran",
+            &["Maria Gonzalez", "Kevin Tran"],
+            &[],
+        ),
+        (
+            "Maria Gonzalez (Kevin Tran - training)",
+            &["Maria Gonzalez"],
+            &["Kevin Tran"],
+        ),
+        ("Maria Gonzalez - 2 vans", &["Maria Gonzalez"], &[]),
+        ("Maria (w/ Kevin)", &["Maria"], &["Kevin"]),
+        ("Maria G. / Kevin T.", &["Maria G.", "Kevin T."], &[]),
+        ("Customer Pickup", &["Customer Pickup"], &[]),
+        // Booked under the event
+        ("Patio Party", &["Patio Party"], &[]),
+        ("Patio party (Sam)", &["Patio party (Sam)"], &[]),
+    ];
+
+    #[test]
+    fn reads_every_regression_case() {
+        let failures: Vec<String> = CASES
+            .iter()
+            .filter_map(|(field, leads, helpers)| {
+                let parsed = parse_drivers(field);
+
+                (parsed.leads != *leads || parsed.helpers != *helpers)
+                    .then(|| format!("{:?}: {:?}", field, parsed))
+            })
+            .collect();
+
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+
+    #[test]
+    fn keeps_notes_to_report() {
+        assert_eq!(parse_drivers("Ann - Bo").notes, vec!["Bo"]);
+        assert_eq!(
+            parse_drivers("John [van 2] (Sam - first week), Ann # call").notes,
+            vec!["van 2", "first week", "call"]
+        );
+        assert!(parse_drivers("John (no helper), *Ann*").notes.is_empty());
+    }
+
+    #[test]
+    fn tokenizes_conjunctions_on_word_boundaries() {
+        assert_eq!(
+            tokenize("Sandra and Bo w/ Cy"),
+            vec![
+                Token::Word("Sandra".to_string()),
+                Token::Separator,
+                Token::Word("Bo".to_string()),
+                Token::With,
+                Token::Word("Cy".to_string()),
+            ]
+        );
+    }
+}
@@ -1,63 +1,45 @@
-use crate::deserialize::Order;
-
-fn get_drivers(order: &Order) -> Vec<String> {
-    let mut drivers: Vec<String> = Vec::new();
-
-    let mut driver = order.employee.to_string();
-
-    if driver.to_lowercase().contains("patio party") {
-        drivers.push(driver);
-        return drivers;
-    }
-
-    if let Some(start) = driver.find("(") {
-        if let Some(end) = driver.find(")") {
-            {
-                let extra = driver[start + 1..end].to_string();
-                driver = driver[..start].trim().to_string();
-
-                drivers.push(extra);
-            }
-        }
-    }
-
-    if driver.contains(",") {
-        let split = driver.split(",");
-
-        split.for_each(|d| drivers.push(d.trim().to_string()));
-        return drivers;
-    } else if driver.contains("and") {
-        let split = driver.split("and");
-
-        split.for_each(|d| drivers.push(d.trim().to_string()));
-        return drivers;
+use crate::{
+    deserialize::{Order, Role},
+    drivers::parse_drivers,
+};
+
+/// Everyone booked on the order with their part in it, drivers before helpers. A cell
+/// naming nobody is kept as it is
+fn get_drivers(order: &Order) -> Vec<(String, Role)> {
+    let drivers = parse_drivers(&order.employee);
+
+    if drivers.leads.is_empty() {
+        return vec![(order.employee.trim().to_string(), Role::Lead)];
     }
 
-    drivers.push(driver);
+    let lead = match drivers.leads.len() {
+        1 => Role::Lead,
+        _ => Role::CoDriver,
+    };
 
     drivers
+        .leads
+        .into_iter()
+        .map(|name| (name, lead))
+        .chain(drivers.helpers.into_iter().map(|name| (name, Role::Helper)))
+        .collect()
 }
 
+/// One row per person booked on each order, marked with their role
 pub fn expand_orders(orders: &[Order]) -> Vec<Order> {
     let mut expanded: Vec<Order> = Vec::new();
 
     for order in orders.iter() {
-        let mut drivers = get_drivers(order);
+        let drivers = get_drivers(order);
 
         let was_expanded = drivers.len() > 1;
 
-        if was_expanded {
-            drivers.reverse(); // Put helpers last
-        }
-
-        for driver in drivers.iter() {
+        for (driver, role) in drivers {
             let mut new_order = order.clone();
 
-            if was_expanded {
-                new_order.expanded = true;
-            }
-
-            new_order.employee = driver.to_string();
+            new_order.expanded = was_expanded;
+            new_order.employee = driver;
+            new_order.role = role;
 
             expanded.push(new_order);
         }
@@ -69,11 +51,12 @@ pub fn expand_orders(orders: &[Order]) -> Vec<Order> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::{TimeZone, Utc};
+    use crate::deserialize::RecordSource;
+    use chrono::{NaiveDate, TimeZone, Utc};
 
     fn base_order_with_employee(emp: &str) -> Order {
         Order {
-            date: 1.0,
+            date: NaiveDate::from_ymd_opt(2025, 1, 1),
             employee: emp.to_string(),
             client: "".to_string(),
             description: "".to_string(),
@@ -81,49 +64,93 @@ mod tests {
             grat: 0.0,
             origin: "".to_string(),
             event: "".to_string(),
-            ready: 0.0,
+            ready: None,
             total: 0.0,
-            datetime: Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+            datetime: Some(Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()),
             expanded: false,
+            role: Role::Lead,
+            source: RecordSource::default(),
         }
     }
 
+    fn roles(drivers: &[(String, Role)]) -> Vec<(&str, Role)> {
+        drivers
+            .iter()
+            .map(|(name, role)| (name.as_str(), *role))
+            .collect()
+    }
+
     #[test]
     fn get_drivers_single() {
         let order = base_order_with_employee("John Doe");
         let drivers = get_drivers(&order);
-        assert_eq!(drivers, vec!["John Doe"]);
+        assert_eq!(roles(&drivers), vec![("John Doe", Role::Lead)]);
     }
 
     #[test]
     fn get_drivers_comma_and_and() {
         let order = base_order_with_employee("Alice, Bob");
         let drivers = get_drivers(&order);
-        assert_eq!(drivers, vec!["Alice", "Bob"]);
+        assert_eq!(
+            roles(&drivers),
+            vec![("Alice", Role::CoDriver), ("Bob", Role::CoDriver)]
+        );
 
         let order2 = base_order_with_employee("Alice and Bob");
         let drivers2 = get_drivers(&order2);
-        assert_eq!(drivers2, vec!["Alice", "Bob"]);
+        assert_eq!(roles(&drivers2), roles(&drivers));
     }
 
     #[test]
     fn get_drivers_parentheses() {
         let order = base_order_with_employee("John (Helper)");
         let drivers = get_drivers(&order);
-        // helper first, then the main driver
-        assert_eq!(drivers, vec!["Helper", "John"]);
+        // A bare role names nobody, so John drove alone
+        assert_eq!(roles(&drivers), vec![("John", Role::Lead)]);
+    }
+
+    #[test]
+    fn get_drivers_parentheses_helper() {
+        let order = base_order_with_employee("John (Sam)");
+        let drivers = get_drivers(&order);
+        // The driver first, then the helper
+        assert_eq!(
+            roles(&drivers),
+            vec![("John", Role::Lead), ("Sam", Role::Helper)]
+        );
+    }
+
+    #[test]
+    fn get_drivers_keeps_cells_naming_nobody() {
+        let order = base_order_with_employee(" TBD ");
+        let drivers = get_drivers(&order);
+        assert_eq!(roles(&drivers), vec![("TBD", Role::Lead)]);
     }
 
     #[test]
     fn expand_orders_expands_and_marks() {
-        let order = base_order_with_employee("Alice and Bob");
-        let expanded = expand_orders(&[order]);
-        // "Alice and Bob" is split into two; expand_orders reverses drivers before producing rows
-        assert_eq!(expanded.len(), 2);
-        // expanded flag set on both produced rows
-        assert!(expanded[0].expanded && expanded[1].expanded);
-        // order of produced employees should be reversed (helpers last in original get_drivers)
-        let employees: Vec<_> = expanded.iter().map(|o| o.employee.clone()).collect();
-        assert_eq!(employees, vec!["Bob".to_string(), "Alice".to_string()]);
+        let orders = [
+            base_order_with_employee("Alice and Bob (Cy)"),
+            base_order_with_employee("Dee"),
+        ];
+        let expanded = expand_orders(&orders);
+
+        // Only rows split from a shared order are marked
+        let marked: Vec<bool> = expanded.iter().map(|o| o.expanded).collect();
+        assert_eq!(marked, vec![true, true, true, false]);
+
+        let employees: Vec<(&str, Role)> = expanded
+            .iter()
+            .map(|o| (o.employee.as_str(), o.role))
+            .collect();
+        assert_eq!(
+            employees,
+            vec![
+                ("Alice", Role::CoDriver),
+                ("Bob", Role::CoDriver),
+                ("Cy", Role::Helper),
+                ("Dee", Role::Lead),
+            ]
+        );
     }
 }
@@ -1,38 +1,180 @@
-use serde::Serialize;
-use serde_json::{json, Value};
-use std::{env, path::PathBuf, sync::Mutex};
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Mutex};
 use tauri::State;
 use tauri_plugin_opener::reveal_item_in_dir;
 
 use crate::{
+    compare::{MatchMode, MatchOptions, MatchWindow},
     constants::{CATEREASE_HEADERS, INTUIT_HEADERS},
-    deserialize::{Order, TimeActivity},
-    stats::get_driver_stats,
-    util::{get_filename, get_orders, get_path, get_references, get_timesheet},
-    write::WorkbookWriter,
+    deserialize::{merge_imported, ImportIssue, IntuitMode, Order, TimeActivity},
+    overrides::Overrides,
+    roster::Roster,
+    settings::Settings,
+    timezone::{parse_timezone, DstPolicy},
+    util::{
+        get_filename, get_orders, get_output_path, get_path, get_timesheet, preview_payroll,
+        process_payroll,
+    },
+    validate::Finding,
 };
 
 #[derive(Clone, Default)]
 pub struct AppState {
     pub caterease: Vec<Order>,
+    /// Cells skipped or defaulted while reading the Caterease file
+    pub caterease_issues: Vec<ImportIssue>,
     pub intuit: Vec<TimeActivity>,
+    /// Cells skipped or defaulted while reading the Intuit file
+    pub intuit_issues: Vec<ImportIssue>,
+    pub roster: Roster,
+    /// Manual match corrections for the loaded orders
+    pub overrides: Overrides,
+    /// Sidecar the overrides are saved to, next to the Caterease file
+    pub overrides_path: Option<PathBuf>,
+    pub settings: Settings,
+    /// Where settings are persisted. None keeps them in memory only
+    pub settings_path: Option<PathBuf>,
 }
 
-use crate::stats::DriverStats;
+impl AppState {
+    pub fn save_settings(&self) -> anyhow::Result<()> {
+        match &self.settings_path {
+            Some(path) => self.settings.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Import issues from both linked files
+    pub fn import_issues(&self) -> Vec<ImportIssue> {
+        self.caterease_issues
+            .iter()
+            .chain(self.intuit_issues.iter())
+            .cloned()
+            .collect()
+    }
+}
+
+use crate::{compare::PreparedRow, stats::DriverStats};
 
+/// What `submit` did. Nothing is written when the output file is already there, so the caller
+/// can ask before submitting again with `overwrite`
 #[derive(Serialize)]
-struct ProcessResult {
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum SubmitResult {
+    Written(Box<ProcessResult>),
+    Exists { path: PathBuf },
+}
+
+#[derive(Serialize)]
+pub struct ProcessResult {
     /// Orders with multiple drivers
-    expanded: usize,
+    pub expanded: usize,
     /// Total matched times to orders
-    matched: u32,
+    pub matched: u32,
     /// Invalid orders
-    skipped: u32,
+    pub skipped: u32,
     /// Total processed rows
-    total: usize,
+    pub total: usize,
     /// Driver statistics (flattened)
     #[serde(flatten)]
-    stats: DriverStats,
+    pub stats: DriverStats,
+    /// Every processed row with its match outcome
+    pub rows: Vec<PreparedRow>,
+    /// Cells skipped or defaulted while importing
+    pub import_issues: usize,
+    /// Order and timesheet warnings to review. Errors stop the export instead
+    pub findings: Vec<Finding>,
+    /// Date spans of both inputs
+    pub pay_periods: PayPeriods,
+}
+
+/// First and last dates of each linked input
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayPeriods {
+    /// Order dates. None when no order has a date
+    pub orders: Option<(NaiveDate, NaiveDate)>,
+    /// Clock-in dates, in the business timezone
+    pub timesheet: Option<(NaiveDate, NaiveDate)>,
+    /// Set when the spans don't overlap or are too far apart
+    pub warning: Option<String>,
+}
+
+/// Optional knobs for `submit`. Anything left out falls back to the saved settings
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SubmitOptions {
+    /// Minutes a clock-in may come before the ready time. Overrides precision
+    before_minutes: Option<i64>,
+    /// Minutes a clock-in may come after the ready time. Overrides precision
+    after_minutes: Option<i64>,
+    output_dir: Option<String>,
+    filename_template: Option<String>,
+    match_mode: Option<MatchMode>,
+    /// Minimum similarity for fuzzy name matches, from 0 to 1
+    fuzzy_threshold: Option<f64>,
+    /// Days the two inputs' pay periods may differ by. Saved for later runs
+    period_tolerance_days: Option<i64>,
+    /// Refuse to export when the pay periods differ. Saved for later runs
+    block_period_mismatch: Option<bool>,
+    /// Replace the output file if it already exists
+    overwrite: bool,
+}
+
+impl SubmitOptions {
+    /// Explicit minute bounds take priority over the symmetric precision in hours, and
+    /// anything not given comes from the saved settings
+    fn match_options(&self, precision: usize, settings: &Settings) -> anyhow::Result<MatchOptions> {
+        let mut window = MatchWindow::from_hours(precision as i64);
+        if let Some(before) = self.before_minutes {
+            window.before_minutes = before;
+        }
+        if let Some(after) = self.after_minutes {
+            window.after_minutes = after;
+        }
+
+        if window.before_minutes < 0 || window.after_minutes < 0 {
+            return Err(anyhow!("Window bounds can't be negative"));
+        }
+
+        if let Some(threshold) = self.fuzzy_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err(anyhow!("Fuzzy threshold must be between 0 and 1"));
+            }
+        }
+
+        Ok(MatchOptions {
+            window,
+            mode: self.match_mode.unwrap_or(settings.match_mode),
+            fuzzy_threshold: self.fuzzy_threshold.or(settings.fuzzy_threshold),
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct PreviewResult {
+    /// Every processed row with its match outcome
+    pub rows: Vec<PreparedRow>,
+    /// Time activities no order claimed
+    pub unmatched: Vec<TimeActivity>,
+    pub stats: DriverStats,
+    /// Order and timesheet warnings and errors to review before exporting
+    pub findings: Vec<Finding>,
+    /// Date spans of both inputs
+    pub pay_periods: PayPeriods,
+}
+
+/// What a linked file contributed
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportResult {
+    file_name: String,
+    /// Orders or shifts added, after dropping duplicates
+    records: usize,
+    /// Cells that were skipped or defaulted, and rows dropped as duplicates
+    issues: Vec<ImportIssue>,
 }
 
 #[derive(Serialize)]
@@ -49,75 +191,221 @@ pub fn get_headers() -> HeaderResult {
     }
 }
 
+/// Links a Caterease file. With `append`, its orders are added to the ones already linked and
+/// the first file's overrides stay in use
 #[tauri::command]
 pub fn caterease_input(
     file_path: String,
+    append: Option<bool>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<String, String> {
+) -> Result<ImportResult, String> {
     let mut state = state.lock().unwrap();
 
     let path = get_path(&file_path).map_err(|e| e.to_string())?;
 
-    let orders = get_orders(&file_path).map_err(|e| e.to_string())?;
+    let orders = get_orders(&file_path, &state.settings).map_err(|e| e.to_string())?;
+
+    if !append.unwrap_or(false) || state.caterease.is_empty() {
+        let overrides_path = Overrides::sidecar_path(&path);
+        let overrides = Overrides::load(&overrides_path).map_err(|e| e.to_string())?;
 
-    state.caterease = orders;
+        state.caterease.clear();
+        state.caterease_issues.clear();
+        state.overrides = overrides;
+        state.overrides_path = Some(overrides_path);
+    }
 
-    let file_name = get_filename(&path);
+    let state = &mut *state;
+    let first_issue = state.caterease_issues.len();
+    let added = merge_imported(&mut state.caterease, &mut state.caterease_issues, orders);
 
-    Ok(file_name)
+    Ok(ImportResult {
+        file_name: get_filename(&path),
+        records: added,
+        issues: state.caterease_issues[first_issue..].to_vec(),
+    })
 }
 
+/// Links an Intuit file. With `append`, its shifts are added to the ones already linked. A
+/// given `mode` is saved and used for later imports too
 #[tauri::command]
 pub fn intuit_input(
     file_path: String,
+    append: Option<bool>,
+    mode: Option<IntuitMode>,
     state: State<'_, Mutex<AppState>>,
-) -> Result<String, String> {
+) -> Result<ImportResult, String> {
     let mut state = state.lock().unwrap();
 
     let path = get_path(&file_path).map_err(|e| e.to_string())?;
 
-    let timesheets = get_timesheet(&file_path).map_err(|e| e.to_string())?;
+    if let Some(mode) = mode {
+        state.settings.intuit_mode = mode;
+        state.save_settings().map_err(|e| e.to_string())?;
+    }
+
+    let timesheets = get_timesheet(&file_path, &state.settings).map_err(|e| e.to_string())?;
 
-    state.intuit = timesheets;
+    if !append.unwrap_or(false) {
+        state.intuit.clear();
+        state.intuit_issues.clear();
+    }
 
-    let file_name = get_filename(&path);
+    let state = &mut *state;
+    let first_issue = state.intuit_issues.len();
+    let added = merge_imported(&mut state.intuit, &mut state.intuit_issues, timesheets);
 
-    Ok(file_name)
+    Ok(ImportResult {
+        file_name: get_filename(&path),
+        records: added,
+        issues: state.intuit_issues[first_issue..].to_vec(),
+    })
 }
 
 #[tauri::command]
-pub fn submit(precision: usize, state: State<'_, Mutex<AppState>>) -> Result<Value, String> {
+pub fn roster_input(file_path: String, state: State<'_, Mutex<AppState>>) -> Result<usize, String> {
     let mut state = state.lock().unwrap();
 
-    let referenced = get_references(precision as i64, &mut state).map_err(|e| e.to_string())?;
+    let path = get_path(&file_path).map_err(|e| e.to_string())?;
 
-    let total = referenced.rows.len();
+    let roster = Roster::load(&path).map_err(|e| e.to_string())?;
+    let count = roster.employees.len();
 
-    let mut excel_writer = WorkbookWriter::new();
-    excel_writer
-        .write_prepared(&referenced.rows)
-        .map_err(|e| e.to_string())?;
-    excel_writer
-        .write_unmatched(&state.intuit)
+    state.roster = roster;
+    state.settings.roster_path = Some(path);
+    state.save_settings().map_err(|e| e.to_string())?;
+
+    Ok(count)
+}
+
+#[tauri::command]
+pub fn get_overrides(state: State<'_, Mutex<AppState>>) -> Overrides {
+    state.lock().unwrap().overrides.clone()
+}
+
+/// Replaces the manual overrides and saves them next to the Caterease file
+#[tauri::command]
+pub fn set_overrides(
+    overrides: Overrides,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<(), String> {
+    let mut state = state.lock().unwrap();
+
+    let path = state
+        .overrides_path
+        .clone()
+        .ok_or("Link a Caterease file before adding overrides")?;
+
+    overrides.save(&path).map_err(|e| e.to_string())?;
+    state.overrides = overrides;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, Mutex<AppState>>) -> Settings {
+    state.lock().unwrap().settings.clone()
+}
+
+/// Saves the business timezone and, when given, how DST transitions are read. Linked files
+/// were read with the old ones, so a change unlinks them. Returns whether it did
+#[tauri::command]
+pub fn set_timezone(
+    timezone: String,
+    dst_policy: Option<DstPolicy>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<bool, String> {
+    let mut state = state.lock().unwrap();
+
+    let timezone = parse_timezone(&timezone).map_err(|e| e.to_string())?;
+    let dst_policy = dst_policy.unwrap_or(state.settings.dst_policy);
+
+    let changed = state.settings.timezone() != timezone || state.settings.dst_policy != dst_policy;
+
+    state.settings.timezone = timezone.name().to_string();
+    state.settings.dst_policy = dst_policy;
+    state.save_settings().map_err(|e| e.to_string())?;
+
+    let linked = !state.caterease.is_empty() || !state.intuit.is_empty();
+    if changed && linked {
+        state.caterease.clear();
+        state.caterease_issues.clear();
+        state.intuit.clear();
+        state.intuit_issues.clear();
+    }
+
+    Ok(changed && linked)
+}
+
+#[tauri::command]
+pub fn submit(
+    precision: usize,
+    options: Option<SubmitOptions>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<SubmitResult, String> {
+    let mut state = state.lock().unwrap();
+    let options = options.unwrap_or_default();
+
+    let match_options = options
+        .match_options(precision, &state.settings)
         .map_err(|e| e.to_string())?;
 
-    let mut path = PathBuf::from(env::var("USERPROFILE").unwrap());
-    path.push("Documents");
-    path.push("formatted_payroll.xlsx");
+    // The run uses the new settings, but they're only kept once it succeeds
+    let previous = state.settings.clone();
+    if let Some(dir) = &options.output_dir {
+        state.settings.output_dir = Some(PathBuf::from(dir));
+    }
+    if let Some(template) = &options.filename_template {
+        state.settings.filename_template = template.clone();
+    }
+    state.settings.match_mode = match_options.mode;
+    state.settings.fuzzy_threshold = match_options.fuzzy_threshold;
+    if let Some(days) = options.period_tolerance_days {
+        state.settings.period_tolerance_days = days.max(0);
+    }
+    if let Some(block) = options.block_period_mismatch {
+        state.settings.block_period_mismatch = block;
+    }
+
+    let written = get_output_path(&state.settings, &state.caterease).and_then(|path| {
+        if path.exists() && !options.overwrite {
+            return Ok((path, None));
+        }
+        let result = process_payroll(&match_options, &state, &path, options.overwrite)?;
+        Ok((path, Some(result)))
+    });
 
-    excel_writer.save(&path).map_err(|e| e.to_string())?;
+    match written {
+        Ok((path, Some(result))) => {
+            state.save_settings().map_err(|e| e.to_string())?;
+            reveal_item_in_dir(path).map_err(|e| e.to_string())?;
 
-    let stats = get_driver_stats(&referenced.rows);
+            Ok(SubmitResult::Written(Box::new(result)))
+        }
+        Ok((path, None)) => {
+            state.settings = previous;
+            Ok(SubmitResult::Exists { path })
+        }
+        Err(e) => {
+            state.settings = previous;
+            Err(e.to_string())
+        }
+    }
+}
 
-    let result = ProcessResult {
-        expanded: total - state.caterease.len(),
-        matched: referenced.matched,
-        skipped: referenced.skipped,
-        total,
-        stats,
-    };
+/// Runs the matcher and returns the results without writing anything to disk
+#[tauri::command]
+pub fn preview(
+    precision: usize,
+    options: Option<SubmitOptions>,
+    state: State<'_, Mutex<AppState>>,
+) -> Result<PreviewResult, String> {
+    let state = state.lock().unwrap();
+    let options = options.unwrap_or_default();
 
-    reveal_item_in_dir(path).unwrap();
+    let match_options = options
+        .match_options(precision, &state.settings)
+        .map_err(|e| e.to_string())?;
 
-    Ok(json!(result))
+    preview_payroll(&match_options, &state).map_err(|e| e.to_string())
 }
@@ -2,14 +2,31 @@ use std::sync::Mutex;
 
 use tauri::{Builder, Manager};
 
-use crate::handlers::{caterease_input, get_headers, intuit_input, submit, AppState};
+use crate::{
+    handlers::{
+        caterease_input, get_headers, get_overrides, get_settings, intuit_input, preview,
+        roster_input, set_overrides, set_timezone, submit, AppState,
+    },
+    roster::Roster,
+    settings::{Settings, SETTINGS_FILE},
+};
 
+pub use crate::cli::run_cli;
+
+mod cli;
 mod compare;
 mod constants;
 mod deserialize;
+mod drivers;
 mod expand;
 mod handlers;
+mod overrides;
+mod roster;
+mod settings;
 mod stats;
+#[cfg(test)]
+mod test_util;
+mod timezone;
 mod util;
 mod validate;
 mod write;
@@ -18,7 +35,23 @@ mod write;
 pub fn run() {
     Builder::default()
         .setup(|app| {
-            app.manage(Mutex::new(AppState::default()));
+            let settings_path = app.path().app_config_dir()?.join(SETTINGS_FILE);
+
+            let settings = Settings::load(&settings_path);
+
+            // A roster that has since moved or broken shouldn't stop the app from starting
+            let roster = settings
+                .roster_path
+                .as_deref()
+                .and_then(|path| Roster::load(path).ok())
+                .unwrap_or_default();
+
+            app.manage(Mutex::new(AppState {
+                roster,
+                settings,
+                settings_path: Some(settings_path),
+                ..Default::default()
+            }));
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
@@ -28,7 +61,13 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             caterease_input,
             get_headers,
+            get_overrides,
+            get_settings,
             intuit_input,
+            preview,
+            roster_input,
+            set_overrides,
+            set_timezone,
             submit
         ])
         .run(tauri::generate_context!())
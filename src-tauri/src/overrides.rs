@@ -0,0 +1,212 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::deserialize::{Order, TimeActivity};
+
+/// Identifies one (expanded) order across re-imports of the same Caterease export
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderKey {
+    pub date: NaiveDate,
+    pub client: String,
+    /// Sub-Event #
+    pub event: String,
+    /// Driver after expansion, so each driver on a shared order can be pinned separately
+    pub employee: String,
+}
+
+impl OrderKey {
+    pub fn of(order: &Order) -> Option<Self> {
+        Some(Self {
+            date: order.date?,
+            client: order.client.clone(),
+            event: order.event.clone(),
+            employee: order.employee.clone(),
+        })
+    }
+
+    pub fn matches(&self, order: &Order) -> bool {
+        Self::of(order).is_some_and(|key| {
+            key.date == self.date
+                && key.client.trim() == self.client.trim()
+                && key.event.trim() == self.event.trim()
+                && key
+                    .employee
+                    .trim()
+                    .eq_ignore_ascii_case(self.employee.trim())
+        })
+    }
+}
+
+/// Identifies one time activity across re-imports of the same Intuit export
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShiftKey {
+    pub first_name: String,
+    pub last_name: String,
+    pub in_time: DateTime<Utc>,
+}
+
+impl ShiftKey {
+    pub fn of(time_activity: &TimeActivity) -> Self {
+        Self {
+            first_name: time_activity.first_name.clone(),
+            last_name: time_activity.last_name.clone(),
+            in_time: time_activity.in_time,
+        }
+    }
+
+    pub fn matches(&self, time_activity: &TimeActivity) -> bool {
+        self.in_time == time_activity.in_time
+            && self
+                .first_name
+                .eq_ignore_ascii_case(&time_activity.first_name)
+            && self
+                .last_name
+                .eq_ignore_ascii_case(&time_activity.last_name)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pin {
+    pub order: OrderKey,
+    pub shift: ShiftKey,
+}
+
+/// Manual corrections applied on top of the matcher
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Overrides {
+    /// Orders that always take a specific shift
+    pub pins: Vec<Pin>,
+    /// Orders that are never matched
+    pub unmatched_orders: Vec<OrderKey>,
+    /// Shifts that are intentionally left unmatched
+    pub ignored_shifts: Vec<ShiftKey>,
+}
+
+impl Overrides {
+    /// Sidecar file kept next to the Caterease export, e.g. `orders.overrides.json`
+    pub fn sidecar_path(orders_path: &Path) -> PathBuf {
+        let stem = orders_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        orders_path.with_file_name(format!("{}.overrides.json", stem))
+    }
+
+    /// Reads overrides from disk. A missing sidecar means there are no overrides yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to open overrides: {}", path.to_string_lossy()))?;
+
+        serde_json::from_str(&contents).context("Overrides file is not valid JSON")
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents).context("Couldn't save overrides")
+    }
+
+    pub fn pin_for(&self, order: &Order) -> Option<&ShiftKey> {
+        self.pins
+            .iter()
+            .find(|pin| pin.order.matches(order))
+            .map(|pin| &pin.shift)
+    }
+
+    pub fn is_forced_unmatched(&self, order: &Order) -> bool {
+        self.unmatched_orders.iter().any(|key| key.matches(order))
+    }
+
+    pub fn is_ignored(&self, time_activity: &TimeActivity) -> bool {
+        self.ignored_shifts
+            .iter()
+            .any(|key| key.matches(time_activity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        deserialize::{RecordSource, Role},
+        test_util::temp_dir,
+    };
+    use chrono::{NaiveTime, TimeZone};
+
+    fn order() -> Order {
+        Order {
+            date: NaiveDate::from_ymd_opt(2025, 3, 3),
+            employee: "Ann Lee".to_string(),
+            client: "Acme".to_string(),
+            description: "".to_string(),
+            count: 0,
+            grat: 0.0,
+            origin: "".to_string(),
+            event: "12".to_string(),
+            ready: NaiveTime::from_hms_opt(12, 0, 0),
+            total: 0.0,
+            datetime: Some(Utc.with_ymd_and_hms(2025, 3, 3, 20, 0, 0).unwrap()),
+            expanded: false,
+            role: Role::Lead,
+            source: RecordSource::default(),
+        }
+    }
+
+    #[test]
+    fn order_key_uses_date_client_event_and_driver() {
+        let key = OrderKey::of(&order()).unwrap();
+        assert_eq!(key.date, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        assert!(key.matches(&order()));
+
+        let mut other_driver = order();
+        other_driver.employee = "Bob".to_string();
+        assert!(!key.matches(&other_driver));
+
+        let mut other_event = order();
+        other_event.event = "13".to_string();
+        assert!(!key.matches(&other_event));
+    }
+
+    #[test]
+    fn sidecar_sits_next_to_orders() {
+        let path = Path::new("/exports/week 10.xlsx");
+        assert_eq!(
+            Overrides::sidecar_path(path),
+            PathBuf::from("/exports/week 10.overrides.json")
+        );
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_dir().join("overrides.json");
+        let overrides = Overrides {
+            pins: vec![],
+            unmatched_orders: vec![OrderKey::of(&order()).unwrap()],
+            ignored_shifts: vec![ShiftKey {
+                first_name: "Ann".to_string(),
+                last_name: "Lee".to_string(),
+                in_time: Utc.with_ymd_and_hms(2025, 3, 3, 19, 0, 0).unwrap(),
+            }],
+        };
+
+        overrides.save(&path).unwrap();
+        assert_eq!(Overrides::load(&path).unwrap(), overrides);
+        let _ = fs::remove_file(&path);
+
+        assert_eq!(Overrides::load(&path).unwrap(), Overrides::default());
+    }
+}
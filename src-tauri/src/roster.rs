@@ -0,0 +1,181 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// One employee, keyed by their Intuit username
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Employee {
+    /// Intuit username, the stable key for time activities
+    pub username: String,
+    /// Canonical full name
+    pub name: String,
+    /// Other spellings and nicknames used in Caterease's Delivery Person column
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Maps Caterease driver spellings to Intuit usernames
+///
+/// Loaded from a JSON file shaped like:
+/// `{ "employees": [{ "username": "jdoe", "name": "Jonathan Doe", "aliases": ["Jon"] }] }`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Roster {
+    pub employees: Vec<Employee>,
+}
+
+/// Lowercases and collapses whitespace so spellings compare loosely
+pub(crate) fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+impl Roster {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to open roster: {}", path.to_string_lossy()))?;
+
+        let roster: Roster =
+            serde_json::from_str(&contents).context("Roster file is not valid JSON")?;
+
+        roster.validate()?;
+
+        Ok(roster)
+    }
+
+    fn validate(&self) -> Result<()> {
+        let mut seen = HashSet::new();
+
+        for employee in self.employees.iter() {
+            if employee.username.trim().is_empty() {
+                return Err(anyhow!("Roster entry '{}' has no username", employee.name));
+            }
+
+            if !seen.insert(employee.username.to_lowercase()) {
+                return Err(anyhow!(
+                    "Duplicate username in roster: '{}'",
+                    employee.username
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Usernames a Caterease driver spelling refers to. Full names and aliases are checked
+    /// first, then a bare first name if only one employee has it. Empty when the roster
+    /// doesn't know the driver.
+    pub fn resolve(&self, driver: &str) -> Vec<&str> {
+        let driver = normalize(driver);
+        if driver.is_empty() {
+            return Vec::new();
+        }
+
+        let exact: Vec<&str> = self
+            .employees
+            .iter()
+            .filter(|employee| {
+                normalize(&employee.name) == driver
+                    || employee
+                        .aliases
+                        .iter()
+                        .any(|alias| normalize(alias) == driver)
+            })
+            .map(|employee| employee.username.as_str())
+            .collect();
+
+        if !exact.is_empty() {
+            return exact;
+        }
+
+        let by_first_name: Vec<&str> = self
+            .employees
+            .iter()
+            .filter(|employee| normalize(&employee.name).split(' ').next() == Some(driver.as_str()))
+            .map(|employee| employee.username.as_str())
+            .collect();
+
+        if by_first_name.len() == 1 {
+            return by_first_name;
+        }
+
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn employee(username: &str, name: &str, aliases: &[&str]) -> Employee {
+        Employee {
+            username: username.to_string(),
+            name: name.to_string(),
+            aliases: aliases.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    fn roster() -> Roster {
+        Roster {
+            employees: vec![
+                employee("jdoe", "Jonathan Doe", &["Jon", "Jon Doe"]),
+                employee("sdoe", "Sam Doe", &[]),
+                employee("mgarcia", "Maria Garcia-Lopez", &["Maria Lopez", "Mari"]),
+                employee("mjones", "Maria Jones", &[]),
+            ],
+        }
+    }
+
+    #[test]
+    fn resolves_names_and_aliases() {
+        let roster = roster();
+
+        assert_eq!(roster.resolve("jonathan doe"), vec!["jdoe"]);
+        assert_eq!(roster.resolve("Jon"), vec!["jdoe"]);
+        assert_eq!(roster.resolve("  jon   doe "), vec!["jdoe"]);
+        assert_eq!(roster.resolve("maria lopez"), vec!["mgarcia"]);
+        assert_eq!(roster.resolve("Mari"), vec!["mgarcia"]);
+    }
+
+    #[test]
+    fn shared_last_names_do_not_cross_match() {
+        let roster = roster();
+
+        assert_eq!(roster.resolve("sam doe"), vec!["sdoe"]);
+        assert!(roster.resolve("doe").is_empty());
+    }
+
+    #[test]
+    fn first_name_only_when_unique() {
+        let roster = roster();
+
+        assert_eq!(roster.resolve("sam"), vec!["sdoe"]);
+        // Two Marias, so a bare first name is ambiguous
+        assert!(roster.resolve("maria").is_empty());
+        assert!(roster.resolve("nobody").is_empty());
+    }
+
+    #[test]
+    fn rejects_duplicate_and_missing_usernames() {
+        let duplicate = Roster {
+            employees: vec![employee("jdoe", "A", &[]), employee("JDoe", "B", &[])],
+        };
+        assert!(duplicate.validate().is_err());
+
+        let missing = Roster {
+            employees: vec![employee(" ", "A", &[])],
+        };
+        assert!(missing.validate().is_err());
+    }
+
+    #[test]
+    fn parses_json() {
+        let json = r#"{ "employees": [{ "username": "jdoe", "name": "Jonathan Doe" }] }"#;
+        let roster: Roster = serde_json::from_str(json).unwrap();
+
+        assert_eq!(roster.employees[0].username, "jdoe");
+        assert!(roster.employees[0].aliases.is_empty());
+    }
+}
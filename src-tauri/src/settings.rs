@@ -0,0 +1,199 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    compare::MatchMode,
+    constants::COLUMN_SYNONYMS,
+    deserialize::{ImportOptions, IntuitMode},
+    timezone::{parse_timezone, DstPolicy, DEFAULT_TIMEZONE},
+};
+
+pub const SETTINGS_FILE: &str = "settings.json";
+
+/// Extra header names for each field, keyed by the field's standard header
+pub type ColumnSynonyms = BTreeMap<String, Vec<String>>;
+
+/// User preferences that persist between runs
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Settings {
+    /// Folder the formatted workbook is saved to. Falls back to the user's Documents folder
+    pub output_dir: Option<PathBuf>,
+    /// Output file name. `{start}` and `{end}` are replaced with the pay period dates
+    pub filename_template: String,
+    /// Last matching strategy used
+    pub match_mode: MatchMode,
+    /// Minimum similarity for fuzzy name matches. None turns fuzzy matching off
+    pub fuzzy_threshold: Option<f64>,
+    /// Employee roster used for name matching, reloaded on startup
+    pub roster_path: Option<PathBuf>,
+    /// Extra header names to accept for each field, on top of the built-in ones
+    pub column_synonyms: ColumnSynonyms,
+    /// IANA name of the kitchen's timezone, used to read and write local times
+    pub timezone: String,
+    /// How local times that fall in a DST transition are read
+    pub dst_policy: DstPolicy,
+    /// Whether Intuit shifts are split into their per-job rows
+    pub intuit_mode: IntuitMode,
+    /// Days the orders' and timesheet's first or last dates may differ before it's reported
+    pub period_tolerance_days: i64,
+    /// Refuse to export when the orders and timesheet cover different pay periods
+    pub block_period_mismatch: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            output_dir: None,
+            filename_template: "formatted_payroll_{start}_{end}.xlsx".to_string(),
+            match_mode: MatchMode::default(),
+            fuzzy_threshold: None,
+            roster_path: None,
+            column_synonyms: ColumnSynonyms::new(),
+            timezone: DEFAULT_TIMEZONE.to_string(),
+            dst_policy: DstPolicy::default(),
+            intuit_mode: IntuitMode::default(),
+            period_tolerance_days: 3,
+            block_period_mismatch: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Reads settings from disk, using the defaults when the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Couldn't create settings folder")?;
+        }
+
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(path, contents).context("Couldn't save settings")
+    }
+
+    /// Built-in header synonyms merged with the configured ones
+    pub fn synonyms(&self) -> ColumnSynonyms {
+        let mut synonyms = self.column_synonyms.clone();
+
+        for (field, names) in COLUMN_SYNONYMS.iter() {
+            synonyms
+                .entry(field.to_string())
+                .or_default()
+                .extend(names.iter().map(|name| name.to_string()));
+        }
+
+        synonyms
+    }
+
+    /// The configured timezone. A name that no longer parses falls back to the default
+    pub fn timezone(&self) -> Tz {
+        parse_timezone(&self.timezone).unwrap_or_else(|_| {
+            parse_timezone(DEFAULT_TIMEZONE).expect("the default timezone is a valid IANA name")
+        })
+    }
+
+    /// How the linked exports are read
+    pub fn import_options(&self) -> ImportOptions {
+        ImportOptions {
+            synonyms: self.synonyms(),
+            timezone: self.timezone(),
+            dst_policy: self.dst_policy,
+            intuit_mode: self.intuit_mode,
+        }
+    }
+
+    /// The configured output folder, or Documents under the user's home folder
+    pub fn resolve_output_dir(&self) -> PathBuf {
+        if let Some(dir) = &self.output_dir {
+            return dir.clone();
+        }
+
+        match env::var_os("USERPROFILE").or_else(|| env::var_os("HOME")) {
+            Some(home) => PathBuf::from(home).join("Documents"),
+            None => env::current_dir().unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::temp_dir;
+
+    #[test]
+    fn missing_file_loads_defaults() {
+        let path = temp_dir().join(SETTINGS_FILE);
+
+        assert_eq!(Settings::load(&path), Settings::default());
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let path = temp_dir().join("config").join(SETTINGS_FILE);
+
+        let settings = Settings {
+            output_dir: Some(PathBuf::from("/tmp/payroll")),
+            filename_template: "week_{start}.xlsx".to_string(),
+            match_mode: MatchMode::Optimal,
+            fuzzy_threshold: Some(0.8),
+            roster_path: Some(PathBuf::from("/tmp/roster.json")),
+            column_synonyms: ColumnSynonyms::from([(
+                "Kitchen Ready by".to_string(),
+                vec!["Out the door".to_string()],
+            )]),
+            timezone: "America/Denver".to_string(),
+            dst_policy: DstPolicy::Flag,
+            intuit_mode: IntuitMode::Jobs,
+            period_tolerance_days: 1,
+            block_period_mismatch: true,
+        };
+        settings.save(&path).unwrap();
+
+        assert_eq!(Settings::load(&path), settings);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn settings_saved_before_match_modes_stay_greedy() {
+        let path = temp_dir().join(SETTINGS_FILE);
+        fs::write(&path, r#"{ "filenameTemplate": "payroll.xlsx" }"#).unwrap();
+
+        let settings = Settings::load(&path);
+        assert_eq!(settings.filename_template, "payroll.xlsx");
+        assert_eq!(settings.match_mode, MatchMode::Greedy);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn configured_output_dir_wins() {
+        let settings = Settings {
+            output_dir: Some(PathBuf::from("/exports")),
+            ..Default::default()
+        };
+
+        assert_eq!(settings.resolve_output_dir(), PathBuf::from("/exports"));
+    }
+
+    #[test]
+    fn unknown_timezone_falls_back_to_default() {
+        let settings = Settings {
+            timezone: "Nowhere/Special".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(settings.timezone(), chrono_tz::America::Los_Angeles);
+    }
+}
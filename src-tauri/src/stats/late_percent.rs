@@ -16,8 +16,8 @@ pub fn find_highest_late_percent_driver(acc: &HashMap<String, DriverAccumulator>
         }
     }
     if max_percent == 0.0 {
-        println!("\n=== Highest Late Percent Driver ===");
-        println!("  No data available");
+        eprintln!("\n=== Highest Late Percent Driver ===");
+        eprintln!("  No data available");
         return (String::new(), 0.0);
     }
     // Second pass: collect all drivers with that percentage (up to 3)
@@ -42,8 +42,8 @@ pub fn find_highest_late_percent_driver(acc: &HashMap<String, DriverAccumulator>
             .join(", "),
         max_percent,
     );
-    println!("\n=== Highest Late Percent Driver ===");
-    println!("  Winner: {} at {:.2}% late", result.0, result.1);
+    eprintln!("\n=== Highest Late Percent Driver ===");
+    eprintln!("  Winner: {} at {:.2}% late", result.0, result.1);
     result
 }
 
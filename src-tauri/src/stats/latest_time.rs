@@ -10,8 +10,8 @@ pub fn find_latest_clock_in_driver(acc: &HashMap<String, DriverAccumulator>) ->
         .max()
         .unwrap_or(0);
     if max_late_diff == 0 {
-        println!("\n=== Latest Single Clock-In ===");
-        println!("  No late clock-ins recorded");
+        eprintln!("\n=== Latest Single Clock-In ===");
+        eprintln!("  No late clock-ins recorded");
         return (String::new(), 0.0);
     }
     let mut winners: Vec<&String> = acc
@@ -30,8 +30,8 @@ pub fn find_latest_clock_in_driver(acc: &HashMap<String, DriverAccumulator>) ->
             .join(", "),
         minutes,
     );
-    println!("\n=== Latest Single Clock-In ===");
-    println!("  Winner: {} at {:.2} minutes late", result.0, result.1);
+    eprintln!("\n=== Latest Single Clock-In ===");
+    eprintln!("  Winner: {} at {:.2} minutes late", result.0, result.1);
     result
 }
 
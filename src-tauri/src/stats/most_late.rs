@@ -23,8 +23,8 @@ pub fn find_most_late_driver(acc: &HashMap<String, DriverAccumulator>) -> (Strin
             .join(", "),
         max_late,
     );
-    println!("\n=== Most Late Driver ===");
-    println!("  Winner: {} with {} late clock-ins", result.0, result.1);
+    eprintln!("\n=== Most Late Driver ===");
+    eprintln!("  Winner: {} with {} late clock-ins", result.0, result.1);
     result
 }
 
@@ -23,8 +23,8 @@ pub fn find_most_used_driver(acc: &HashMap<String, DriverAccumulator>) -> (Strin
             .join(", "),
         max_count,
     );
-    println!("\n=== Most Used Driver ===");
-    println!("  Winner: {} with {} deliveries", result.0, result.1);
+    eprintln!("\n=== Most Used Driver ===");
+    eprintln!("  Winner: {} with {} deliveries", result.0, result.1);
     result
 }
 
@@ -1,5 +1,6 @@
 use crate::{
     compare::PreparedRow,
+    deserialize::Role,
     stats::{
         late_percent::find_highest_late_percent_driver,
         latest_time::find_latest_clock_in_driver,
@@ -9,8 +10,10 @@ use crate::{
         util::{calculate_time_difference, normalize_driver_name, update_driver_stats},
         DriverAccumulator, DriverStats,
     },
+    timezone::DstPolicy,
 };
 
+use chrono_tz::Tz;
 use std::collections::HashMap;
 
 /// Determine the most punctual driver based on two factors:
@@ -19,14 +22,15 @@ use std::collections::HashMap;
 ///
 /// Logic:
 /// 1. Filter rows that have a suggested_in time.
-/// 2. Convert Excel serial `ready` value (f64) to a `DateTime<Utc>`.
+/// 2. Convert the local `ready` time in `timezone` to a `DateTime<Utc>`, reading DST
+///    transitions by `policy`.
 /// 3. Compute signed difference in seconds (positive = late, negative = early).
 /// 4. Aggregate per driver: count late instances and track average diff.
 /// 5. Rank by: (1) fewest late clock-ins, then (2) smallest average difference.
 ///
 /// If no rows have a suggested_in time this returns an empty string.
-pub fn get_driver_stats(rows: &[PreparedRow]) -> DriverStats {
-    let acc = build_driver_accumulator(rows);
+pub fn get_driver_stats(rows: &[PreparedRow], timezone: Tz, policy: DstPolicy) -> DriverStats {
+    let acc = build_driver_accumulator(rows, timezone, policy);
 
     let (top_used, top_used_count) = find_most_used_driver(&acc);
     let (most_late, most_late_count) = find_most_late_driver(&acc);
@@ -50,16 +54,21 @@ pub fn get_driver_stats(rows: &[PreparedRow]) -> DriverStats {
     }
 }
 
-/// Process all rows and accumulate statistics for each driver
-fn build_driver_accumulator(rows: &[PreparedRow]) -> HashMap<String, DriverAccumulator> {
+/// Process all rows and accumulate statistics for each driver. Helpers ride along with the
+/// driver, so their clock-ins don't count towards anyone's deliveries
+fn build_driver_accumulator(
+    rows: &[PreparedRow],
+    timezone: Tz,
+    policy: DstPolicy,
+) -> HashMap<String, DriverAccumulator> {
     let mut acc: HashMap<String, DriverAccumulator> = HashMap::new();
 
-    println!("\n=== Building Driver Accumulator ===");
-    println!("Total rows to process: {}", rows.len());
+    eprintln!("\n=== Building Driver Accumulator ===");
+    eprintln!("Total rows to process: {}", rows.len());
 
-    for row in rows {
+    for row in rows.iter().filter(|row| row.order.role != Role::Helper) {
         let driver = normalize_driver_name(&row.order.employee);
-        if let Some(diff_seconds) = calculate_time_difference(row) {
+        if let Some(diff_seconds) = calculate_time_difference(row, timezone, policy) {
             let entry = acc
                 .entry(driver.clone())
                 .or_insert_with(DriverAccumulator::default);
@@ -67,7 +76,7 @@ fn build_driver_accumulator(rows: &[PreparedRow]) -> HashMap<String, DriverAccum
         }
     }
 
-    println!("\n=== Driver Summary ===");
+    eprintln!("\n=== Driver Summary ===");
     let mut driver_vec: Vec<_> = acc.iter().collect();
     driver_vec.sort_by_key(|(_, v)| std::cmp::Reverse(v.count));
     for (driver, v) in driver_vec {
@@ -77,10 +86,92 @@ fn build_driver_accumulator(rows: &[PreparedRow]) -> HashMap<String, DriverAccum
         let avg_minutes = (v.total_diff_seconds as f64 / v.count as f64) / 60.0;
         let late_percent = (v.late_count as f64 / v.count as f64) * 100.0;
         let latest_minutes = v.max_late_diff_seconds as f64 / 60.0;
-        println!(
+        eprintln!(
             "  {}: {} deliveries, {} late ({:.1}%), avg diff: {:.2} min, latest: {:.2} min late",
             driver, v.count, v.late_count, late_percent, avg_minutes, latest_minutes
         );
     }
     acc
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compare::MatchOutcome,
+        deserialize::{Order, RecordSource},
+    };
+    use chrono::{NaiveDate, NaiveTime, TimeZone, Utc};
+
+    const PACIFIC: Tz = chrono_tz::America::Los_Angeles;
+
+    /// A matched row for an order ready at noon Pacific, clocked in at 12:10
+    fn matched(employee: &str, role: Role) -> PreparedRow {
+        PreparedRow {
+            order: Order {
+                date: NaiveDate::from_ymd_opt(2025, 3, 3),
+                employee: employee.to_string(),
+                client: String::new(),
+                description: String::new(),
+                count: 0,
+                grat: 0.0,
+                origin: String::new(),
+                event: String::new(),
+                ready: NaiveTime::from_hms_opt(12, 0, 0),
+                total: 0.0,
+                datetime: Some(Utc.with_ymd_and_hms(2025, 3, 3, 20, 0, 0).unwrap()),
+                expanded: role != Role::Lead,
+                role,
+                source: RecordSource::default(),
+            },
+            hours: 1.0,
+            miles: 0.0,
+            suggested_in: Some(Utc.with_ymd_and_hms(2025, 3, 3, 20, 10, 0).unwrap()),
+            suggested_out: Some(Utc.with_ymd_and_hms(2025, 3, 3, 21, 10, 0).unwrap()),
+            shift: None,
+            name_match: None,
+            outcome: MatchOutcome::Matched,
+            delta_minutes: Some(10.0),
+            candidate_count: 1,
+        }
+    }
+
+    #[test]
+    fn helpers_are_not_ranked() {
+        let rows = vec![
+            matched("Ann Lee", Role::CoDriver),
+            matched("Bo Diaz", Role::CoDriver),
+            matched("Cy Ward", Role::Helper),
+            matched("Cy Ward", Role::Helper),
+        ];
+
+        let stats = get_driver_stats(&rows, PACIFIC, DstPolicy::Earliest);
+
+        assert_eq!(stats.top_used, "Ann, Bo");
+        assert_eq!(stats.top_used_count, 1);
+        assert_eq!(stats.most_late, "Ann, Bo");
+    }
+
+    #[test]
+    fn fall_back_ready_times_follow_policy() {
+        // Ready at 1:30 AM on 2025-11-02, which happens twice in Los Angeles. Clocked in at
+        // 1:40 AM PDT, so the first 1:30 is 10 minutes earlier and the second 50 minutes later
+        let mut row = matched("Ann Lee", Role::Lead);
+        row.order.date = NaiveDate::from_ymd_opt(2025, 11, 2);
+        row.order.ready = NaiveTime::from_hms_opt(1, 30, 0);
+        row.suggested_in = Some(Utc.with_ymd_and_hms(2025, 11, 2, 8, 40, 0).unwrap());
+        let rows = vec![row];
+
+        let earliest = get_driver_stats(&rows, PACIFIC, DstPolicy::Earliest);
+        assert_eq!(earliest.top_used_count, 1);
+        assert_eq!(earliest.most_late_count, 1);
+
+        let latest = get_driver_stats(&rows, PACIFIC, DstPolicy::Latest);
+        assert_eq!(latest.top_used_count, 1);
+        assert_eq!(latest.most_late_count, 0);
+
+        // Flagged ready times are left out rather than guessed
+        let flagged = get_driver_stats(&rows, PACIFIC, DstPolicy::Flag);
+        assert_eq!(flagged.top_used_count, 0);
+    }
+}
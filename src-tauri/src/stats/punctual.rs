@@ -34,16 +34,16 @@ pub fn find_most_punctual_driver(acc: &HashMap<String, DriverAccumulator>) -> (S
         }
     }
     let result = best.unwrap_or_default();
-    println!("\n=== Most Punctual Driver ===");
+    eprintln!("\n=== Most Punctual Driver ===");
     if !result.0.is_empty() {
-        println!(
+        eprintln!(
             "  Winner: {} with {} late clock-ins, avg diff: {:.2} min",
             result.0, result.2, result.1
         );
     } else {
-        println!("  No driver qualified (need minimum 3 deliveries)");
+        eprintln!("  No driver qualified (need minimum 3 deliveries)");
     }
-    println!("===================================\n");
+    eprintln!("===================================\n");
     result
 }
 
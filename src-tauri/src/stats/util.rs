@@ -1,6 +1,11 @@
-use chrono::{DateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
 
-use crate::{compare::PreparedRow, stats::DriverAccumulator};
+use crate::{
+    compare::PreparedRow,
+    stats::DriverAccumulator,
+    timezone::{resolve_local, DstPolicy},
+};
 
 /// Normalize driver name to first word only
 pub fn normalize_driver_name(employee: &str) -> String {
@@ -13,25 +18,33 @@ pub fn normalize_driver_name(employee: &str) -> String {
 
 /// Calculate the signed difference in seconds between suggested clock-in and kitchen ready time
 /// Returns None if the row doesn't have valid data
-pub fn calculate_time_difference(row: &PreparedRow) -> Option<i64> {
+pub fn calculate_time_difference(
+    row: &PreparedRow,
+    timezone: Tz,
+    policy: DstPolicy,
+) -> Option<i64> {
     let suggested_in = row.suggested_in?;
-    let serial = row.order.ready;
 
-    if serial <= 0.0 {
-        println!(
-            "  [SKIP] {}: Invalid ready serial ({}) - no suggested_in",
-            normalize_driver_name(&row.order.employee),
-            serial
+    let Some(ready) = row.order.ready else {
+        eprintln!(
+            "  [SKIP] {}: Missing ready time",
+            normalize_driver_name(&row.order.employee)
         );
         return None;
-    }
+    };
 
-    let ready_utc = convert_excel_serial_to_utc(serial, suggested_in)?;
+    let Some(ready_utc) = convert_ready_to_utc(ready, suggested_in, timezone, policy) else {
+        eprintln!(
+            "  [SKIP] {}: Ready time falls in a DST transition",
+            normalize_driver_name(&row.order.employee)
+        );
+        return None;
+    };
     let diff_seconds = suggested_in.signed_duration_since(ready_utc).num_seconds();
 
     // Skip unrealistic diffs (greater than 24h) as likely data errors
     if diff_seconds.abs() > 86_400 {
-        println!(
+        eprintln!(
             "  [SKIP] {}: Unrealistic time diff ({} hours)",
             normalize_driver_name(&row.order.employee),
             diff_seconds / 3600
@@ -42,30 +55,19 @@ pub fn calculate_time_difference(row: &PreparedRow) -> Option<i64> {
     Some(diff_seconds)
 }
 
-/// Convert Excel serial time to UTC DateTime, using the clock-in date
-pub fn convert_excel_serial_to_utc(
-    serial: f64,
+/// Convert the kitchen ready time to UTC DateTime, using the local clock-in date. Returns None
+/// when the time falls in a DST transition and the policy is to flag it
+pub fn convert_ready_to_utc(
+    ready: NaiveTime,
     suggested_in: DateTime<Utc>,
+    timezone: Tz,
+    policy: DstPolicy,
 ) -> Option<DateTime<Utc>> {
-    // Extract time-of-day from Excel serial (fractional part only, no date)
-    let fractional = serial - serial.floor();
-    let seconds_in_day = (fractional * 86_400.0).round() as u32;
-    let hours = seconds_in_day / 3600;
-    let minutes = (seconds_in_day % 3600) / 60;
-    let seconds = seconds_in_day % 60;
-
-    // Use the clock-in date and construct ready time in LA timezone
-    let clock_in_date = suggested_in.date_naive();
-    let ready_naive = clock_in_date
-        .and_hms_opt(hours, minutes, seconds)
-        .unwrap_or_else(|| clock_in_date.and_hms_opt(0, 0, 0).unwrap());
+    // Use the clock-in date and construct ready time in the business timezone
+    let clock_in_date = suggested_in.with_timezone(&timezone).date_naive();
+    let ready_naive = clock_in_date.and_time(ready);
 
-    // Convert LA time to UTC
-    chrono_tz::America::Los_Angeles
-        .from_local_datetime(&ready_naive)
-        .single()
-        .map(|dt| dt.with_timezone(&Utc))
-        .or_else(|| Some(Utc.from_utc_datetime(&ready_naive)))
+    resolve_local(ready_naive, timezone, policy).utc
 }
 
 /// Update driver statistics with a new time difference
@@ -0,0 +1,20 @@
+use std::{
+    env, fs,
+    path::PathBuf,
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// An empty folder under the system temp folder that no other test, or test run, shares
+pub fn temp_dir() -> PathBuf {
+    let dir = env::temp_dir().join(format!(
+        "payroll_app_{}_{}",
+        process::id(),
+        NEXT_DIR.fetch_add(1, Ordering::Relaxed)
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
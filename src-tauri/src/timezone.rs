@@ -0,0 +1,215 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Business timezone used until one is configured
+pub const DEFAULT_TIMEZONE: &str = "America/Los_Angeles";
+
+/// How a local time that falls in a DST transition is read
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DstPolicy {
+    /// The earlier of the two possible instants
+    #[default]
+    Earliest,
+    /// The later of the two possible instants
+    Latest,
+    /// Leave the time unread so someone can check it
+    Flag,
+}
+
+/// Why a local time couldn't be read as a single instant
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DstTransition {
+    /// The clocks went back, so the time happened twice
+    Ambiguous,
+    /// The clocks went forward, so the time never happened
+    Nonexistent,
+}
+
+/// A local time read as UTC
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Resolved {
+    /// None when the policy is to flag the time
+    pub utc: Option<DateTime<Utc>>,
+    /// Set when the time fell in a DST transition
+    pub transition: Option<DstTransition>,
+}
+
+impl Resolved {
+    /// Warning for the import issues, None for an ordinary time
+    pub fn warning(&self, policy: DstPolicy) -> Option<String> {
+        let cause = match self.transition? {
+            DstTransition::Ambiguous => "Time happens twice when DST ends",
+            DstTransition::Nonexistent => "Time is skipped when DST starts",
+        };
+
+        let outcome = match policy {
+            DstPolicy::Earliest => "read as the earlier instant",
+            DstPolicy::Latest => "read as the later instant",
+            DstPolicy::Flag => "needs review",
+        };
+
+        Some(format!("{}, {}", cause, outcome))
+    }
+}
+
+/// Parses an IANA timezone name such as `America/Denver`
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse::<Tz>()
+        .map_err(|_| anyhow!("Unknown timezone: '{}'", name))
+}
+
+/// Reads a local wall-clock time in `timezone`, applying `policy` when it falls in a DST
+/// transition. A skipped time is read with the UTC offset from either side of the gap.
+pub fn resolve_local(naive: NaiveDateTime, timezone: Tz, policy: DstPolicy) -> Resolved {
+    let (earliest, latest, transition) = match timezone.from_local_datetime(&naive) {
+        LocalResult::Single(local) => {
+            return Resolved {
+                utc: Some(local.to_utc()),
+                transition: None,
+            }
+        }
+        LocalResult::Ambiguous(first, second) => {
+            (first.to_utc(), second.to_utc(), DstTransition::Ambiguous)
+        }
+        LocalResult::None => {
+            let day = TimeDelta::days(1);
+            let before = timezone
+                .offset_from_local_datetime(&(naive - day))
+                .earliest();
+            let after = timezone
+                .offset_from_local_datetime(&(naive + day))
+                .earliest();
+
+            let candidates: Vec<DateTime<Utc>> = [before, after]
+                .into_iter()
+                .flatten()
+                .filter_map(|offset| offset.fix().from_local_datetime(&naive).single())
+                .map(|fixed| fixed.to_utc())
+                .collect();
+
+            match (candidates.iter().min(), candidates.iter().max()) {
+                (Some(&earliest), Some(&latest)) => (earliest, latest, DstTransition::Nonexistent),
+                _ => {
+                    return Resolved {
+                        utc: None,
+                        transition: Some(DstTransition::Nonexistent),
+                    }
+                }
+            }
+        }
+    };
+
+    let utc = match policy {
+        DstPolicy::Earliest => Some(earliest),
+        DstPolicy::Latest => Some(latest),
+        DstPolicy::Flag => None,
+    };
+
+    Resolved {
+        utc,
+        transition: Some(transition),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    const PACIFIC: Tz = chrono_tz::America::Los_Angeles;
+
+    fn local(month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    fn utc(month: u32, day: u32, hour: u32, minute: u32) -> Option<DateTime<Utc>> {
+        Utc.with_ymd_and_hms(2025, month, day, hour, minute, 0)
+            .single()
+    }
+
+    #[test]
+    fn parses_iana_names() {
+        assert_eq!(parse_timezone(DEFAULT_TIMEZONE).unwrap(), PACIFIC);
+        assert_eq!(
+            parse_timezone(" America/Denver ").unwrap(),
+            chrono_tz::America::Denver
+        );
+        assert!(parse_timezone("Mars/Olympus").is_err());
+    }
+
+    #[test]
+    fn converts_in_the_configured_zone() {
+        let noon = local(3, 3, 12, 0);
+
+        assert_eq!(
+            resolve_local(noon, PACIFIC, DstPolicy::Earliest).utc,
+            utc(3, 3, 20, 0)
+        );
+        assert_eq!(
+            resolve_local(noon, chrono_tz::America::New_York, DstPolicy::Earliest).utc,
+            utc(3, 3, 17, 0)
+        );
+    }
+
+    #[test]
+    fn ordinary_times_have_no_transition() {
+        for (month, day) in [(3, 9), (11, 2)] {
+            let resolved = resolve_local(local(month, day, 12, 0), PACIFIC, DstPolicy::Flag);
+
+            assert!(resolved.utc.is_some());
+            assert_eq!(resolved.transition, None);
+            assert_eq!(resolved.warning(DstPolicy::Flag), None);
+        }
+    }
+
+    #[test]
+    fn fall_back_follows_policy() {
+        let repeated = local(11, 2, 1, 30);
+
+        let earliest = resolve_local(repeated, PACIFIC, DstPolicy::Earliest);
+        assert_eq!(earliest.utc, utc(11, 2, 8, 30));
+        assert_eq!(earliest.transition, Some(DstTransition::Ambiguous));
+        assert_eq!(
+            earliest.warning(DstPolicy::Earliest).unwrap(),
+            "Time happens twice when DST ends, read as the earlier instant"
+        );
+
+        let latest = resolve_local(repeated, PACIFIC, DstPolicy::Latest);
+        assert_eq!(latest.utc, utc(11, 2, 9, 30));
+
+        let flagged = resolve_local(repeated, PACIFIC, DstPolicy::Flag);
+        assert_eq!(flagged.utc, None);
+        assert_eq!(
+            flagged.warning(DstPolicy::Flag).unwrap(),
+            "Time happens twice when DST ends, needs review"
+        );
+    }
+
+    #[test]
+    fn spring_forward_follows_policy() {
+        // 2:30 AM never happens on 2025-03-09 in Los Angeles
+        let skipped = local(3, 9, 2, 30);
+
+        let earliest = resolve_local(skipped, PACIFIC, DstPolicy::Earliest);
+        assert_eq!(earliest.utc, utc(3, 9, 9, 30));
+        assert_eq!(earliest.transition, Some(DstTransition::Nonexistent));
+        assert_eq!(
+            earliest.warning(DstPolicy::Earliest).unwrap(),
+            "Time is skipped when DST starts, read as the earlier instant"
+        );
+
+        let latest = resolve_local(skipped, PACIFIC, DstPolicy::Latest);
+        assert_eq!(latest.utc, utc(3, 9, 10, 30));
+
+        let flagged = resolve_local(skipped, PACIFIC, DstPolicy::Flag);
+        assert_eq!(flagged.utc, None);
+        assert_eq!(flagged.transition, Some(DstTransition::Nonexistent));
+    }
+}
@@ -1,23 +1,37 @@
 use crate::{
-    compare::{cross_reference_orders, ReferenceResult},
-    deserialize::{deserialize_caterease_excel, deserialize_intuit_excel, Order, TimeActivity},
+    compare::{cross_reference_orders, MatchOptions, ReferenceResult},
+    constants::WORKBOOK_EXTENSIONS,
+    deserialize::{deserialize_caterease, deserialize_intuit, Imported, Order, TimeActivity},
     expand::expand_orders,
-    handlers::AppState,
-    validate::{validate_order_input, validate_time_input},
+    handlers::{AppState, PayPeriods, PreviewResult, ProcessResult},
+    settings::Settings,
+    stats::get_driver_stats,
+    validate::{
+        check_orders, check_pay_periods, check_roster_usernames, check_time_activities,
+        refuse_errors, validate_order_input, validate_time_input, Finding,
+    },
+    write::WorkbookWriter,
 };
 
 use anyhow::{anyhow, Result};
-use std::{
-    path::{Path, PathBuf},
-    sync::MutexGuard,
-};
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use std::path::{Path, PathBuf};
 
+/// File name for display, without the extension of a supported import format
 pub fn get_filename(path: &Path) -> String {
-    path.file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string()
-        .replace(".xlsx", "")
+    let supported = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .is_some_and(|ext| ext == "csv" || WORKBOOK_EXTENSIONS.contains(&ext.as_str()));
+
+    let name = if supported {
+        path.file_stem()
+    } else {
+        path.file_name()
+    };
+
+    name.unwrap_or_default().to_string_lossy().to_string()
 }
 
 pub fn get_path(file_path: &str) -> Result<PathBuf> {
@@ -30,38 +44,406 @@ pub fn get_path(file_path: &str) -> Result<PathBuf> {
     Ok(path.to_path_buf())
 }
 
-pub fn get_orders(file_path: &str) -> Result<Vec<Order>> {
-    let orders: Vec<Order> = match deserialize_caterease_excel(file_path) {
+pub fn get_orders(file_path: &str, settings: &Settings) -> Result<Imported<Order>> {
+    let orders: Imported<Order> = match deserialize_caterease(file_path, &settings.import_options())
+    {
         Ok(res) => res,
         Err(e) => return Err(anyhow!(e)),
     };
 
-    validate_order_input(&orders)?;
+    validate_order_input(&orders.records)?;
 
     Ok(orders)
 }
 
-pub fn get_timesheet(file_path: &str) -> Result<Vec<TimeActivity>> {
-    let timesheets: Vec<TimeActivity> = match deserialize_intuit_excel(file_path) {
-        Ok(res) => res,
-        Err(e) => return Err(anyhow!(e)),
-    };
+pub fn get_timesheet(file_path: &str, settings: &Settings) -> Result<Imported<TimeActivity>> {
+    let timesheets: Imported<TimeActivity> =
+        match deserialize_intuit(file_path, &settings.import_options()) {
+            Ok(res) => res,
+            Err(e) => return Err(anyhow!(e)),
+        };
 
-    validate_time_input(&timesheets)?;
+    validate_time_input(&timesheets.records)?;
 
     Ok(timesheets)
 }
 
-pub fn get_references(
-    precision: i64,
-    state: &mut MutexGuard<'_, AppState>,
-) -> Result<ReferenceResult> {
+/// Matches the linked documents. Works on a copy of the timesheet, so the loaded state is left
+/// untouched and the same inputs always give the same result.
+pub fn get_references(options: &MatchOptions, state: &AppState) -> Result<ReferenceResult> {
     if state.caterease.is_empty() || state.intuit.is_empty() {
         return Err(anyhow!("Both documents must be linked"));
     }
     let mut expanded = expand_orders(&state.caterease);
 
-    let reference_result = cross_reference_orders(&mut expanded, &mut state.intuit, precision);
+    let mut time_sheets = state.intuit.clone();
+    for time_activity in time_sheets.iter_mut() {
+        time_activity.matched = false;
+        time_activity.ignored = false;
+    }
+
+    Ok(cross_reference_orders(
+        &mut expanded,
+        time_sheets,
+        options,
+        &state.roster,
+        &state.overrides,
+    ))
+}
+
+/// Problems found in the loaded orders, then in the loaded timesheet
+pub fn get_findings(state: &AppState) -> Vec<Finding> {
+    let mut findings = check_orders(&state.caterease, &state.roster);
+    findings.extend(check_time_activities(&state.intuit));
+    findings.extend(check_roster_usernames(&state.roster, &state.intuit));
+
+    findings
+}
+
+/// Matches the linked documents without touching disk, for review before exporting
+pub fn preview_payroll(options: &MatchOptions, state: &AppState) -> Result<PreviewResult> {
+    let referenced = get_references(options, state)?;
+
+    let stats = get_driver_stats(
+        &referenced.rows,
+        state.settings.timezone(),
+        state.settings.dst_policy,
+    );
+    let findings = get_findings(state);
+    let pay_periods = get_pay_periods(state);
+
+    let unmatched = referenced
+        .time_sheets
+        .into_iter()
+        .filter(|time_activity| !time_activity.matched)
+        .collect();
+
+    Ok(PreviewResult {
+        rows: referenced.rows,
+        unmatched,
+        stats,
+        findings,
+        pay_periods,
+    })
+}
+
+/// First and last order dates of the loaded orders, as printed on the export
+pub fn get_pay_period(orders: &[Order]) -> Option<(NaiveDate, NaiveDate)> {
+    let dates = orders.iter().filter_map(|order| order.date);
+
+    let start = dates.clone().min()?;
+    let end = dates.max()?;
+
+    Some((start, end))
+}
+
+/// First and last clock-in dates of the loaded timesheet, in the business timezone
+pub fn get_timesheet_period(
+    timesheets: &[TimeActivity],
+    timezone: Tz,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let dates = timesheets
+        .iter()
+        .map(|time_activity| time_activity.in_time.with_timezone(&timezone).date_naive());
+
+    let start = dates.clone().min()?;
+    let end = dates.max()?;
+
+    Some((start, end))
+}
+
+/// Pay periods of both linked inputs, with a warning when they don't line up
+pub fn get_pay_periods(state: &AppState) -> PayPeriods {
+    let orders = get_pay_period(&state.caterease);
+    let timesheet = get_timesheet_period(&state.intuit, state.settings.timezone());
+
+    let warning = match (orders, timesheet) {
+        (Some(orders), Some(timesheet)) => {
+            check_pay_periods(orders, timesheet, state.settings.period_tolerance_days)
+        }
+        _ => None,
+    };
+
+    PayPeriods {
+        orders,
+        timesheet,
+        warning,
+    }
+}
+
+/// Builds the output path from the settings' folder and filename template. The expanded
+/// name must stay inside that folder
+pub fn get_output_path(settings: &Settings, orders: &[Order]) -> Result<PathBuf> {
+    let mut file_name = settings.filename_template.clone();
+
+    if let Some((start, end)) = get_pay_period(orders) {
+        file_name = file_name
+            .replace("{start}", &start.format("%Y-%m-%d").to_string())
+            .replace("{end}", &end.format("%Y-%m-%d").to_string());
+    }
+
+    if file_name.contains(['/', '\\', ':']) || file_name.trim_matches('.').is_empty() {
+        return Err(anyhow!(
+            "Filename template must be a file name, not a path: {}",
+            file_name
+        ));
+    }
+
+    if !file_name.to_lowercase().ends_with(".xlsx") {
+        file_name.push_str(".xlsx");
+    }
+
+    Ok(settings.resolve_output_dir().join(file_name))
+}
+
+/// Matches the linked documents, writes the workbook to `path` and summarizes the run
+pub fn process_payroll(
+    options: &MatchOptions,
+    state: &AppState,
+    path: &PathBuf,
+    overwrite: bool,
+) -> Result<ProcessResult> {
+    if path.exists() && !overwrite {
+        return Err(anyhow!(
+            "Output file already exists: {}",
+            path.to_string_lossy()
+        ));
+    }
+
+    let findings = get_findings(state);
+    refuse_errors(&findings)?;
+
+    let pay_periods = get_pay_periods(state);
+    if let Some(warning) = pay_periods
+        .warning
+        .as_ref()
+        .filter(|_| state.settings.block_period_mismatch)
+    {
+        return Err(anyhow!("{}", warning));
+    }
+
+    let referenced = get_references(options, state)?;
+
+    let total = referenced.rows.len();
+
+    let timezone = state.settings.timezone();
+
+    let mut excel_writer = WorkbookWriter::new(timezone);
+    excel_writer.write_prepared(&referenced.rows)?;
+    excel_writer.write_unmatched(&referenced.time_sheets)?;
+    excel_writer.write_run_info(options, state.settings.dst_policy)?;
+    excel_writer.write_import_issues(&state.import_issues())?;
+    excel_writer.write_findings(&findings)?;
+    excel_writer.save(path)?;
+
+    let stats = get_driver_stats(&referenced.rows, timezone, state.settings.dst_policy);
+
+    Ok(ProcessResult {
+        expanded: total - state.caterease.len(),
+        matched: referenced.matched,
+        skipped: referenced.skipped,
+        total,
+        stats,
+        rows: referenced.rows,
+        import_issues: state.import_issues().len(),
+        findings,
+        pay_periods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        compare::{MatchMode, MatchWindow},
+        deserialize::{RecordSource, Role},
+        test_util::temp_dir,
+    };
+    use chrono::{NaiveTime, TimeZone, Utc};
+
+    fn order_on(year: i32, month: u32, day: u32) -> Order {
+        Order {
+            date: NaiveDate::from_ymd_opt(year, month, day),
+            employee: "".to_string(),
+            client: "".to_string(),
+            description: "".to_string(),
+            count: 0,
+            grat: 0.0,
+            origin: "".to_string(),
+            event: "".to_string(),
+            ready: None,
+            total: 0.0,
+            datetime: Some(Utc.with_ymd_and_hms(year, month, day, 20, 0, 0).unwrap()),
+            expanded: false,
+            role: Role::Lead,
+            source: RecordSource::default(),
+        }
+    }
+
+    #[test]
+    fn filename_drops_import_extensions() {
+        assert_eq!(get_filename(Path::new("/in/orders.xlsx")), "orders");
+        assert_eq!(get_filename(Path::new("/in/orders.XLS")), "orders");
+        assert_eq!(get_filename(Path::new("/in/week 10.ods")), "week 10");
+        assert_eq!(get_filename(Path::new("/in/times.csv")), "times");
+        assert_eq!(get_filename(Path::new("/in/notes.txt")), "notes.txt");
+    }
+
+    #[test]
+    fn pay_period_spans_orders() {
+        let orders = vec![
+            order_on(2025, 3, 12),
+            order_on(2025, 3, 3),
+            order_on(2025, 3, 9),
+        ];
+
+        let (start, end) = get_pay_period(&orders).unwrap();
+        assert_eq!(start, NaiveDate::from_ymd_opt(2025, 3, 3).unwrap());
+        assert_eq!(end, NaiveDate::from_ymd_opt(2025, 3, 12).unwrap());
+        assert!(get_pay_period(&[]).is_none());
+    }
+
+    #[test]
+    fn output_path_fills_template() {
+        let settings = Settings {
+            output_dir: Some(PathBuf::from("/exports")),
+            filename_template: "payroll_{start}_{end}".to_string(),
+            ..Default::default()
+        };
+        let orders = vec![order_on(2025, 3, 3), order_on(2025, 3, 9)];
+
+        assert_eq!(
+            get_output_path(&settings, &orders).unwrap(),
+            PathBuf::from("/exports/payroll_2025-03-03_2025-03-09.xlsx")
+        );
+    }
+
+    #[test]
+    fn output_path_stays_in_output_dir() {
+        let orders = vec![order_on(2025, 3, 3)];
+
+        for template in [
+            "../payroll.xlsx",
+            "week/{start}.xlsx",
+            "..\\payroll",
+            "C:payroll",
+            "..",
+        ] {
+            let settings = Settings {
+                output_dir: Some(PathBuf::from("/exports")),
+                filename_template: template.to_string(),
+                ..Default::default()
+            };
+
+            assert!(get_output_path(&settings, &orders).is_err(), "{}", template);
+        }
+    }
+
+    fn shift_for(first: &str, last: &str, hour: u32) -> TimeActivity {
+        TimeActivity {
+            first_name: first.to_string(),
+            last_name: last.to_string(),
+            username: last.to_lowercase(),
+            in_time: Utc.with_ymd_and_hms(2025, 3, 3, hour, 30, 0).unwrap(),
+            out_time: Utc.with_ymd_and_hms(2025, 3, 3, hour + 1, 0, 0).unwrap(),
+            hours: 1.5,
+            miles: 4.0,
+            customer: String::new(),
+            shift: None,
+            matched: false,
+            ignored: false,
+            source: RecordSource::default(),
+        }
+    }
+
+    /// One order for Ann Lee at 20:00 UTC with her shift, plus a stray shift for someone else
+    fn linked_state() -> AppState {
+        let mut order = order_on(2025, 3, 3);
+        order.employee = "Ann Lee".to_string();
+        // Noon in Pacific time
+        order.ready = NaiveTime::from_hms_opt(12, 0, 0);
+
+        AppState {
+            caterease: vec![order],
+            intuit: vec![shift_for("Ann", "Lee", 19), shift_for("Cy", "Ward", 19)],
+            ..Default::default()
+        }
+    }
+
+    fn one_hour_greedy() -> MatchOptions {
+        MatchOptions {
+            window: MatchWindow::from_hours(1),
+            mode: MatchMode::Greedy,
+            fuzzy_threshold: None,
+        }
+    }
+
+    #[test]
+    fn references_are_repeatable() {
+        let state = linked_state();
+        let options = one_hour_greedy();
+
+        let first = get_references(&options, &state).unwrap();
+        let second = get_references(&options, &state).unwrap();
+
+        assert_eq!(first.matched, 1);
+        assert_eq!(second.matched, 1);
+        assert!(second.time_sheets[0].matched);
+        assert!(!state.intuit[0].matched);
+    }
+
+    #[test]
+    fn pay_periods_are_compared_when_both_are_linked() {
+        let mut state = linked_state();
+        let periods = get_pay_periods(&state);
+        let march_3 = NaiveDate::from_ymd_opt(2025, 3, 3).unwrap();
 
-    Ok(reference_result)
+        assert_eq!(periods.orders, Some((march_3, march_3)));
+        assert_eq!(periods.timesheet, Some((march_3, march_3)));
+        assert_eq!(periods.warning, None);
+
+        // Last week's timesheet
+        for time_activity in state.intuit.iter_mut() {
+            time_activity.in_time -= chrono::TimeDelta::days(7);
+            time_activity.out_time -= chrono::TimeDelta::days(7);
+        }
+        assert!(get_pay_periods(&state)
+            .warning
+            .unwrap()
+            .ends_with("so they don't overlap"));
+
+        state.caterease.clear();
+        assert!(get_pay_periods(&state).warning.is_none());
+    }
+
+    #[test]
+    fn mismatched_pay_periods_block_when_configured() {
+        let mut state = linked_state();
+        for time_activity in state.intuit.iter_mut() {
+            time_activity.in_time -= chrono::TimeDelta::days(7);
+            time_activity.out_time -= chrono::TimeDelta::days(7);
+        }
+        state.settings.block_period_mismatch = true;
+        let path = temp_dir().join("blocked_period.xlsx");
+
+        let error = process_payroll(&one_hour_greedy(), &state, &path, true)
+            .err()
+            .unwrap();
+
+        assert!(error.to_string().starts_with("The orders run 2025-03-03"));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn preview_returns_rows_unmatched_and_stats() {
+        let state = linked_state();
+
+        let preview = preview_payroll(&one_hour_greedy(), &state).unwrap();
+
+        assert_eq!(preview.rows.len(), 1);
+        assert_eq!(preview.rows[0].hours, 1.5);
+        assert_eq!(preview.unmatched.len(), 1);
+        assert_eq!(preview.unmatched[0].last_name, "Ward");
+        assert_eq!(preview.stats.top_used, "Ann");
+    }
 }
@@ -1,36 +1,125 @@
-use crate::deserialize::{Order, TimeActivity};
-use anyhow::{anyhow, Context, Result};
+use crate::{
+    compare::is_valid_order,
+    deserialize::{Order, RecordSource, TimeActivity},
+    expand::expand_orders,
+    roster::{normalize, Roster},
+    settings::ColumnSynonyms,
+};
+use anyhow::{anyhow, Result};
 use calamine::{Data, DataType, Range};
+use chrono::NaiveDate;
+use serde::Serialize;
+use std::collections::HashMap;
 
-pub fn validate_headers(worksheet: &Range<Data>, expected_headers: &[String]) -> Result<()> {
-    // 1. Improper number of headers
-    if expected_headers.len() != worksheet.width() {
-        return Err(anyhow!(
-            "Improper number of columns in file: Found {}, expected {}",
-            worksheet.width(),
-            expected_headers.len()
-        ));
+/// Hours may differ from the clocked span by this much before it's reported, to allow for
+/// Intuit rounding to the minute
+const HOURS_TOLERANCE: f64 = 0.05;
+
+/// A driver booked on two orders with ready times closer than this is reported
+const DOUBLE_BOOKING_MINUTES: i64 = 30;
+
+/// How serious a problem found in the loaded records is
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// Worth a look, but the export can go ahead
+    Warning,
+    /// The export would be wrong, so it's refused until the input is fixed
+    Error,
+}
+
+/// A problem with a loaded record, tied to the row it was read from
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub severity: Severity,
+    pub source: RecordSource,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, source: &RecordSource, message: String) -> Self {
+        Self {
+            severity,
+            source: source.clone(),
+            message,
+        }
     }
+}
 
-    // 2. Wrong headers (positional check)
-    for (col, expected) in expected_headers.iter().enumerate() {
-        let actual = worksheet
-            .get((0, col))
-            .context(format!("Couldn't read header column {}", col + 1))?
-            .get_string()
-            .context(format!("Header at column {} is not a string", col + 1))?
-            .trim();
-
-        if !actual.eq_ignore_ascii_case(expected) {
-            return Err(anyhow!(
-                "Improper header in file: '{}' (Expected: '{}')",
-                actual,
-                expected
-            ));
+/// Column index of each logical field, resolved from the header row
+#[derive(Debug, Default)]
+pub struct ColumnMap {
+    columns: HashMap<String, usize>,
+}
+
+impl ColumnMap {
+    /// The cell under a field's column, or None if the field has no column in this file
+    pub fn cell<'a>(&self, row: &'a [Data], field: &str) -> Option<&'a Data> {
+        self.columns.get(field).and_then(|col| row.get(*col))
+    }
+
+    /// Whether the file has a column for the field
+    pub fn has(&self, field: &str) -> bool {
+        self.columns.contains_key(field)
+    }
+}
+
+/// Lowercases and collapses whitespace so headers compare loosely
+fn normalize_header(header: &str) -> String {
+    header
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Finds each field's column by its header name or one of its synonyms, in any order.
+/// Columns that don't belong to a field are ignored. Fails listing every required field
+/// with no matching column.
+pub fn map_headers(
+    worksheet: &Range<Data>,
+    fields: &[String],
+    required: &[String],
+    synonyms: &ColumnSynonyms,
+) -> Result<ColumnMap> {
+    let headers: Vec<String> = (0..worksheet.width())
+        .map(|col| {
+            worksheet
+                .get((0, col))
+                .and_then(|cell| cell.get_string())
+                .map(normalize_header)
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut columns = HashMap::new();
+
+    for field in fields.iter() {
+        let names: Vec<String> = std::iter::once(field)
+            .chain(synonyms.get(field).into_iter().flatten())
+            .map(|name| normalize_header(name))
+            .collect();
+
+        if let Some(col) = headers.iter().position(|header| names.contains(header)) {
+            columns.insert(field.clone(), col);
         }
     }
 
-    Ok(())
+    let missing: Vec<&str> = required
+        .iter()
+        .filter(|field| !columns.contains_key(*field))
+        .map(|field| field.as_str())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "Missing required columns in file: {}",
+            missing.join(", ")
+        ));
+    }
+
+    Ok(ColumnMap { columns })
 }
 
 pub fn validate_order_input(orders: &[Order]) -> Result<()> {
@@ -39,17 +128,19 @@ pub fn validate_order_input(orders: &[Order]) -> Result<()> {
         return Err(anyhow!("No orders found in Excel file"));
     }
 
-    // Missing first date
-    let current_date = orders[0].date;
-    if current_date == 45658.0 {
-        return Err(anyhow!("First order date is invalid"));
-    }
+    // Missing valid dates, named by row so the export can be fixed
+    let mut undated = orders.iter().filter(|order| order.date.is_none());
+    if let Some(first) = undated.next() {
+        let more = match undated.count() {
+            0 => String::new(),
+            count => format!(" (and {} more)", count),
+        };
 
-    // Missing valid dates
-    for order in orders.iter() {
-        if order.date == 45658.0 {
-            return Err(anyhow!("Invalid order date found"));
-        }
+        return Err(anyhow!(
+            "Order date is missing or invalid on {}{}",
+            first.source.describe(),
+            more
+        ));
     }
 
     Ok(())
@@ -70,3 +161,620 @@ pub fn validate_time_input(timesheets: &[TimeActivity]) -> Result<()> {
 
     Ok(())
 }
+
+/// Looks for timesheet rows that can't be right: clock-outs before clock-ins, empty shifts,
+/// hours that disagree with the clocked span and overlapping shifts for one employee
+pub fn check_time_activities(timesheets: &[TimeActivity]) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for entry in timesheets.iter() {
+        let clocked = (entry.out_time - entry.in_time).num_seconds() as f64 / 3600.0;
+
+        if clocked < 0.0 {
+            findings.push(Finding::new(
+                Severity::Error,
+                &entry.source,
+                "Clock-out is before clock-in".to_string(),
+            ));
+            continue;
+        }
+
+        if clocked == 0.0 {
+            findings.push(Finding::new(
+                Severity::Warning,
+                &entry.source,
+                "Shift has no length".to_string(),
+            ));
+        }
+
+        if (entry.hours - clocked).abs() > HOURS_TOLERANCE {
+            findings.push(Finding::new(
+                Severity::Warning,
+                &entry.source,
+                format!(
+                    "Hours is {:.2} but the shift lasts {:.2}",
+                    entry.hours, clocked
+                ),
+            ));
+        }
+    }
+
+    // Inverted rows are already reported and would overlap everything around them
+    let mut ordered: Vec<&TimeActivity> = timesheets
+        .iter()
+        .filter(|entry| entry.out_time >= entry.in_time)
+        .collect();
+    ordered.sort_by_key(|entry| entry.in_time);
+
+    for (index, entry) in ordered.iter().enumerate() {
+        let earlier = ordered[..index]
+            .iter()
+            .rev()
+            .find(|other| entry.same_employee(other) && other.out_time > entry.in_time);
+
+        if let Some(other) = earlier {
+            findings.push(Finding::new(
+                Severity::Error,
+                &entry.source,
+                format!("Overlaps {} for the same employee", other.source.describe()),
+            ));
+        }
+    }
+
+    findings
+}
+
+/// Reports timesheet rows the roster can't be used for, since a roster only resolves drivers
+/// to usernames. A timesheet with no usernames at all gets one warning instead of one per row
+pub fn check_roster_usernames(roster: &Roster, timesheets: &[TimeActivity]) -> Vec<Finding> {
+    if roster.employees.is_empty() {
+        return Vec::new();
+    }
+
+    let missing: Vec<&TimeActivity> = timesheets
+        .iter()
+        .filter(|entry| entry.username.trim().is_empty())
+        .collect();
+
+    match missing.first() {
+        Some(first) if missing.len() == timesheets.len() => vec![Finding::new(
+            Severity::Warning,
+            &first.source,
+            "The timesheet has no usernames, so the roster is not used and drivers are matched by name"
+                .to_string(),
+        )],
+        _ => missing
+            .into_iter()
+            .map(|entry| {
+                Finding::new(
+                    Severity::Warning,
+                    &entry.source,
+                    "No username, so the roster is not used for this shift".to_string(),
+                )
+            })
+            .collect(),
+    }
+}
+
+/// Who a booking belongs to: the roster's username when it knows exactly one, otherwise the
+/// name with case and spacing evened out
+fn booking_key(roster: &Roster, driver: &str) -> String {
+    match roster.resolve(driver).as_slice() {
+        [username] => format!("@{}", username.to_lowercase()),
+        _ => normalize(driver),
+    }
+}
+
+/// Looks for orders that need a second look: repeated sub-events, gratuity above the
+/// subtotal, negative amounts and drivers booked twice at once. Every finding is a warning,
+/// since the order may still be right. Missing ready times are left to the import issues
+pub fn check_orders(orders: &[Order], roster: &Roster) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut events: HashMap<&str, &RecordSource> = HashMap::new();
+
+    for order in orders.iter() {
+        let mut warn = |message: String| {
+            findings.push(Finding::new(Severity::Warning, &order.source, message));
+        };
+
+        let event = order.event.trim();
+        if !event.is_empty() {
+            match events.get(event) {
+                Some(first) => warn(format!(
+                    "Sub-Event # {} is also on {}",
+                    event,
+                    first.describe()
+                )),
+                None => {
+                    events.insert(event, &order.source);
+                }
+            }
+        }
+
+        if order.count < 0 {
+            warn(format!("Count is negative ({})", order.count));
+        }
+
+        if order.total < 0.0 {
+            warn(format!("Subtotal is negative ({:.2})", order.total));
+        }
+
+        if order.grat > order.total.max(0.0) {
+            warn(format!(
+                "Gratuity ${:.2} is more than the subtotal ${:.2}",
+                order.grat, order.total
+            ));
+        }
+    }
+
+    // One row per driver, so helpers are checked as well
+    let mut bookings: Vec<(String, Order)> = expand_orders(orders)
+        .into_iter()
+        .filter(|order| order.datetime.is_some() && is_valid_order(&order.employee.to_lowercase()))
+        .map(|order| (booking_key(roster, &order.employee), order))
+        .collect();
+    bookings.sort_by(|(a_key, a), (b_key, b)| a_key.cmp(b_key).then(a.datetime.cmp(&b.datetime)));
+
+    for pair in bookings.windows(2) {
+        let ((earlier_key, earlier), (later_key, later)) = (&pair[0], &pair[1]);
+
+        if earlier_key != later_key || earlier.source == later.source {
+            continue;
+        }
+
+        let (Some(first), Some(second)) = (earlier.datetime, later.datetime) else {
+            continue;
+        };
+        let minutes = (second - first).num_minutes();
+
+        if minutes < DOUBLE_BOOKING_MINUTES {
+            findings.push(Finding::new(
+                Severity::Warning,
+                &later.source,
+                format!(
+                    "{} is also booked on {}, ready {} minutes apart",
+                    later.employee.trim(),
+                    earlier.source.describe(),
+                    minutes
+                ),
+            ));
+        }
+    }
+
+    findings
+}
+
+/// Compares the first and last dates of the orders and the timesheet. Reports spans that
+/// don't overlap, or whose starts or ends are more than `tolerance_days` apart
+pub fn check_pay_periods(
+    orders: (NaiveDate, NaiveDate),
+    timesheet: (NaiveDate, NaiveDate),
+    tolerance_days: i64,
+) -> Option<String> {
+    let spans = format!(
+        "The orders run {} to {} but the timesheet runs {} to {}",
+        orders.0.format("%Y-%m-%d"),
+        orders.1.format("%Y-%m-%d"),
+        timesheet.0.format("%Y-%m-%d"),
+        timesheet.1.format("%Y-%m-%d")
+    );
+
+    if orders.1 < timesheet.0 || timesheet.1 < orders.0 {
+        return Some(format!("{}, so they don't overlap", spans));
+    }
+
+    let apart = (orders.0 - timesheet.0)
+        .num_days()
+        .abs()
+        .max((orders.1 - timesheet.1).num_days().abs());
+
+    let unit = if apart == 1 { "day" } else { "days" };
+
+    (apart > tolerance_days).then(|| format!("{}, {} {} apart", spans, apart, unit))
+}
+
+/// Fails on the first error among the findings, so nothing is exported from bad input
+pub fn refuse_errors(findings: &[Finding]) -> Result<()> {
+    let mut errors = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error);
+
+    let Some(first) = errors.next() else {
+        return Ok(());
+    };
+
+    let more = match errors.count() {
+        0 => String::new(),
+        count => format!(" (and {} more)", count),
+    };
+
+    Err(anyhow!(
+        "Fix the timesheet before exporting: {}, {}{}",
+        first.source.describe(),
+        first.message,
+        more
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{deserialize::Role, roster::Employee};
+    use chrono::{TimeZone, Utc};
+
+    fn sheet(headers: &[&str]) -> Range<Data> {
+        let mut range = Range::new((0, 0), (1, headers.len() as u32 - 1));
+        for (col, header) in headers.iter().enumerate() {
+            range.set_value((0, col as u32), Data::String(header.to_string()));
+            range.set_value((1, col as u32), Data::Int(col as i64));
+        }
+        range
+    }
+
+    fn order_on(date: Option<NaiveDate>) -> Order {
+        Order {
+            date,
+            employee: "Ann Lee".to_string(),
+            client: "".to_string(),
+            description: "".to_string(),
+            count: 0,
+            grat: 0.0,
+            origin: "".to_string(),
+            event: "".to_string(),
+            ready: None,
+            total: 0.0,
+            datetime: None,
+            expanded: false,
+            role: Role::Lead,
+            source: RecordSource::default(),
+        }
+    }
+
+    /// A shift on 2025-03-03 between the given minutes past 18:00 UTC, read from `row`
+    fn shift(username: &str, start: i64, end: i64, hours: f64, row: usize) -> TimeActivity {
+        let six = Utc.with_ymd_and_hms(2025, 3, 3, 18, 0, 0).unwrap();
+
+        TimeActivity {
+            first_name: "Ann".to_string(),
+            last_name: "Lee".to_string(),
+            username: username.to_string(),
+            in_time: six + chrono::TimeDelta::minutes(start),
+            out_time: six + chrono::TimeDelta::minutes(end),
+            hours,
+            miles: 0.0,
+            customer: String::new(),
+            shift: None,
+            matched: false,
+            ignored: false,
+            source: RecordSource {
+                file: "times.csv".to_string(),
+                sheet: "times.csv".to_string(),
+                row,
+            },
+        }
+    }
+
+    fn fields(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn maps_reordered_and_extra_columns() {
+        let worksheet = sheet(&["Notes", "kitchen  ready BY", "Date", "Driver"]);
+        let synonyms =
+            ColumnSynonyms::from([("Delivery Person".to_string(), vec!["Driver".to_string()])]);
+
+        let all = fields(&["Date", "Delivery Person", "Kitchen Ready by", "Grat"]);
+        let required = fields(&["Date", "Delivery Person", "Kitchen Ready by"]);
+        let columns = map_headers(&worksheet, &all, &required, &synonyms).unwrap();
+
+        let row = worksheet.rows().nth(1).unwrap();
+        assert_eq!(columns.cell(row, "Date"), Some(&Data::Int(2)));
+        assert_eq!(columns.cell(row, "Delivery Person"), Some(&Data::Int(3)));
+        assert_eq!(columns.cell(row, "Kitchen Ready by"), Some(&Data::Int(1)));
+        // Optional and absent
+        assert_eq!(columns.cell(row, "Grat"), None);
+    }
+
+    #[test]
+    fn lists_every_missing_required_field() {
+        let worksheet = sheet(&["Date", "Subtotal"]);
+        let required = fields(&["Date", "Delivery Person", "Sub-Event #"]);
+
+        let error = map_headers(&worksheet, &required, &required, &ColumnSynonyms::new())
+            .unwrap_err()
+            .to_string();
+
+        assert_eq!(
+            error,
+            "Missing required columns in file: Delivery Person, Sub-Event #"
+        );
+    }
+
+    #[test]
+    fn new_years_day_is_a_real_date() {
+        let new_year = order_on(NaiveDate::from_ymd_opt(2025, 1, 1));
+        let missing = order_on(None);
+
+        assert!(validate_order_input(std::slice::from_ref(&new_year)).is_ok());
+        assert!(validate_order_input(std::slice::from_ref(&missing)).is_err());
+        assert!(validate_order_input(&[new_year, missing]).is_err());
+    }
+
+    #[test]
+    fn undated_orders_are_named() {
+        let dated = order_on(NaiveDate::from_ymd_opt(2025, 3, 3));
+        let mut undated = order_on(None);
+        undated.source = RecordSource {
+            file: "orders.xlsx".to_string(),
+            sheet: "Sheet1".to_string(),
+            row: 3,
+        };
+
+        assert_eq!(
+            validate_order_input(&[dated, undated.clone(), undated])
+                .unwrap_err()
+                .to_string(),
+            "Order date is missing or invalid on orders.xlsx, Sheet1 row 3 (and 1 more)"
+        );
+    }
+
+    #[test]
+    fn sound_timesheets_have_no_findings() {
+        let timesheets = vec![
+            shift("alee", 0, 90, 1.5, 2),
+            // Back to back is fine, and Intuit rounds to the minute
+            shift("alee", 90, 121, 0.5, 3),
+            shift("cward", 30, 60, 0.5, 4),
+        ];
+
+        assert!(check_time_activities(&timesheets).is_empty());
+    }
+
+    #[test]
+    fn reports_bad_shifts_by_severity() {
+        let timesheets = vec![
+            shift("alee", 60, 0, 1.0, 2),
+            shift("alee", 120, 120, 0.0, 3),
+            shift("alee", 180, 240, 3.0, 4),
+        ];
+
+        let findings = check_time_activities(&timesheets);
+
+        let summary: Vec<(Severity, usize, &str)> = findings
+            .iter()
+            .map(|finding| {
+                (
+                    finding.severity,
+                    finding.source.row,
+                    finding.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Error, 2, "Clock-out is before clock-in"),
+                (Severity::Warning, 3, "Shift has no length"),
+                (
+                    Severity::Warning,
+                    4,
+                    "Hours is 3.00 but the shift lasts 1.00"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn overlapping_shifts_for_one_employee_are_errors() {
+        let mut by_name = shift("", 60, 120, 1.0, 4);
+        by_name.last_name = "LEE".to_string();
+
+        // Someone else at the same time
+        let mut other = shift("cward", 0, 90, 1.5, 3);
+        other.first_name = "Cy".to_string();
+        other.last_name = "Ward".to_string();
+
+        let timesheets = vec![
+            shift("alee", 0, 90, 1.5, 2),
+            other,
+            // No username, so the names are compared
+            by_name,
+        ];
+
+        let findings = check_time_activities(&timesheets);
+
+        assert_eq!(
+            findings,
+            vec![Finding {
+                severity: Severity::Error,
+                source: timesheets[2].source.clone(),
+                message: "Overlaps times.csv row 2 for the same employee".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn roster_needs_timesheet_usernames() {
+        let roster = Roster {
+            employees: vec![Employee {
+                username: "alee".to_string(),
+                name: "Ann Lee".to_string(),
+                aliases: Vec::new(),
+            }],
+        };
+        let blank = [shift("", 0, 60, 1.0, 2), shift(" ", 90, 120, 0.5, 3)];
+        let mixed = [shift("alee", 0, 60, 1.0, 2), shift("", 90, 120, 0.5, 3)];
+
+        let findings = check_roster_usernames(&roster, &blank);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].source.row, 2);
+        assert!(findings[0]
+            .message
+            .starts_with("The timesheet has no usernames"));
+
+        let findings = check_roster_usernames(&roster, &mixed);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].source.row, 3);
+
+        assert!(check_roster_usernames(&Roster::default(), &blank).is_empty());
+    }
+
+    #[test]
+    fn errors_block_and_warnings_pass() {
+        let warning = Finding::new(
+            Severity::Warning,
+            &RecordSource::default(),
+            "Shift has no length".to_string(),
+        );
+        assert!(refuse_errors(std::slice::from_ref(&warning)).is_ok());
+
+        let timesheets = vec![shift("alee", 60, 0, 1.0, 2), shift("alee", 0, 30, 0.5, 3)];
+        let mut findings = check_time_activities(&timesheets);
+        findings.push(findings[0].clone());
+        findings.insert(0, warning);
+
+        assert_eq!(
+            refuse_errors(&findings).unwrap_err().to_string(),
+            "Fix the timesheet before exporting: times.csv row 2, Clock-out is before clock-in (and 1 more)"
+        );
+    }
+
+    /// An order for `employee` ready at the given minutes past noon UTC, read from `row`
+    fn booked(employee: &str, minutes: i64, event: &str, row: usize) -> Order {
+        let noon = Utc.with_ymd_and_hms(2025, 3, 3, 12, 0, 0).unwrap();
+        let ready = noon + chrono::TimeDelta::minutes(minutes);
+
+        Order {
+            employee: employee.to_string(),
+            event: event.to_string(),
+            count: 20,
+            grat: 15.0,
+            total: 300.0,
+            ready: Some(ready.time()),
+            datetime: Some(ready),
+            source: RecordSource {
+                file: "orders.xlsx".to_string(),
+                sheet: "Sheet1".to_string(),
+                row,
+            },
+            ..order_on(ready.date_naive().into())
+        }
+    }
+
+    fn messages(findings: &[Finding]) -> Vec<(usize, &str)> {
+        findings
+            .iter()
+            .map(|finding| {
+                assert_eq!(finding.severity, Severity::Warning);
+                (finding.source.row, finding.message.as_str())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn flags_impossible_order_values() {
+        let mut generous = booked("Bo Diaz", 60, "102", 3);
+        generous.grat = 50.0;
+        generous.total = 40.0;
+
+        let mut refund = booked("Cy Ward", 120, "103", 4);
+        refund.count = -2;
+        refund.total = -5.0;
+        refund.grat = 0.0;
+
+        // Already reported when the file was read
+        let mut unready = booked("Di Fox", 180, "104", 5);
+        unready.ready = None;
+        unready.datetime = None;
+
+        let orders = vec![
+            booked("Ann Lee", 0, "101", 2),
+            generous,
+            refund,
+            unready,
+            booked("Ed Moy", 240, " 101", 7),
+        ];
+
+        assert_eq!(
+            messages(&check_orders(&orders, &Roster::default())),
+            vec![
+                (3, "Gratuity $50.00 is more than the subtotal $40.00"),
+                (4, "Count is negative (-2)"),
+                (4, "Subtotal is negative (-5.00)"),
+                (7, "Sub-Event # 101 is also on orders.xlsx, Sheet1 row 2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_drivers_booked_twice_at_once() {
+        let orders = vec![
+            booked("Ann Lee", 0, "101", 2),
+            // Ann helps on the next order a quarter hour later
+            booked("Bo Diaz (ann lee)", 15, "102", 3),
+            // Far enough apart to drive both
+            booked("Ann Lee", 120, "103", 4),
+            booked("Bo Diaz", 180, "104", 5),
+            // Same person with stray spacing
+            booked("ann  lee ", 130, "105", 6),
+        ];
+
+        assert_eq!(
+            messages(&check_orders(&orders, &Roster::default())),
+            vec![
+                (
+                    3,
+                    "ann lee is also booked on orders.xlsx, Sheet1 row 2, ready 15 minutes apart"
+                ),
+                (
+                    6,
+                    "ann lee is also booked on orders.xlsx, Sheet1 row 4, ready 10 minutes apart"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn double_bookings_follow_roster_aliases() {
+        let roster = Roster {
+            employees: vec![Employee {
+                username: "alee".to_string(),
+                name: "Ann Lee".to_string(),
+                aliases: vec!["Annie".to_string()],
+            }],
+        };
+        let orders = vec![
+            booked("Ann Lee", 0, "101", 2),
+            booked("Annie", 20, "102", 3),
+        ];
+
+        assert_eq!(
+            messages(&check_orders(&orders, &roster)),
+            vec![(
+                3,
+                "Annie is also booked on orders.xlsx, Sheet1 row 2, ready 20 minutes apart"
+            )]
+        );
+        assert!(check_orders(&orders, &Roster::default()).is_empty());
+    }
+
+    #[test]
+    fn pay_periods_must_line_up() {
+        let day = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+        let this_week = (day(3), day(9));
+
+        assert_eq!(check_pay_periods(this_week, this_week, 3), None);
+        // Shifts past midnight end a day late
+        assert_eq!(check_pay_periods(this_week, (day(3), day(10)), 3), None);
+
+        assert_eq!(
+            check_pay_periods(this_week, (day(1), day(13)), 3).unwrap(),
+            "The orders run 2025-03-03 to 2025-03-09 but the timesheet runs 2025-03-01 to 2025-03-13, 4 days apart"
+        );
+        assert_eq!(
+            check_pay_periods(this_week, (day(10), day(16)), 30).unwrap(),
+            "The orders run 2025-03-03 to 2025-03-09 but the timesheet runs 2025-03-10 to 2025-03-16, so they don't overlap"
+        );
+    }
+}
@@ -1,30 +1,50 @@
 use anyhow::Result;
-use chrono::{DateTime, Utc};
-use chrono_tz::US::Pacific;
-use rust_xlsxwriter::{worksheet::Worksheet, ExcelDateTime, Format, FormatAlign};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use rust_xlsxwriter::{worksheet::Worksheet, Format, FormatAlign};
 
+/// Writes the date, or leaves a formatted blank cell when it's missing
 pub fn write_order_date(
     worksheet: &mut Worksheet,
     row: u32,
     col: u16,
-    order_date: f64,
+    order_date: Option<NaiveDate>,
     format: &Format,
 ) -> Result<()> {
-    let excel_dt = ExcelDateTime::from_serial_datetime(order_date)?;
-    worksheet.write_datetime_with_format(row, col, excel_dt, format)?;
+    match order_date {
+        Some(date) => worksheet.write_datetime_with_format(row, col, date, format)?,
+        None => worksheet.write_blank(row, col, format)?,
+    };
 
     Ok(())
 }
 
+/// Writes the time of day, or leaves a formatted blank cell when it's missing
+pub fn write_order_time(
+    worksheet: &mut Worksheet,
+    row: u32,
+    col: u16,
+    order_time: Option<NaiveTime>,
+    format: &Format,
+) -> Result<()> {
+    match order_time {
+        Some(time) => worksheet.write_datetime_with_format(row, col, time, format)?,
+        None => worksheet.write_blank(row, col, format)?,
+    };
+
+    Ok(())
+}
+
+/// Writes the instant as wall-clock time in `timezone`
 pub fn write_order_timestamp(
     worksheet: &mut Worksheet,
     row: u32,
     col: u16,
     timestamp: DateTime<Utc>,
+    timezone: Tz,
     format: &Format,
 ) -> Result<()> {
-    let pacific_time = timestamp.with_timezone(&Pacific);
-    let chrono_dt = pacific_time.naive_local();
+    let chrono_dt = timestamp.with_timezone(&timezone).naive_local();
 
     worksheet.write_datetime_with_format(row, col, chrono_dt, format)?;
 
@@ -49,6 +69,13 @@ pub fn write_header_row(worksheet: &mut Worksheet, row: u32, format: &Format) ->
         vec!["Ready", "Subtotal", "Clock In", "Clock Out"],
         &right_header,
     )?;
+    worksheet.write(row, 14, "Name Rule")?;
+    worksheet.write_with_format(row, 15, "Name Score", &right_header)?;
+    worksheet.write(row, 16, "Outcome")?;
+    worksheet.write_row_with_format(row, 17, vec!["Delta (min)", "Candidates"], &right_header)?;
+    worksheet.write(row, 19, "Source")?;
+    worksheet.write(row, 20, "Role")?;
+    worksheet.write_row_with_format(row, 21, vec!["Shift In", "Shift Out"], &right_header)?;
 
     Ok(())
 }
@@ -1,11 +1,14 @@
 use std::path::PathBuf;
 
 use crate::{
-    compare::PreparedRow,
-    deserialize::TimeActivity,
-    write::util::{write_header_row, write_order_date, write_order_timestamp},
+    compare::{MatchMode, MatchOptions, MatchOutcome, NameRule, PreparedRow},
+    deserialize::{ImportIssue, Role, TimeActivity},
+    timezone::DstPolicy,
+    validate::{Finding, Severity},
+    write::util::{write_header_row, write_order_date, write_order_time, write_order_timestamp},
 };
 use anyhow::{Context, Error, Result};
+use chrono_tz::Tz;
 use rust_xlsxwriter::{workbook::Workbook, Color, Format, FormatAlign, FormatBorder};
 
 const LT_GRAY: u32 = 0xE5E7EB;
@@ -20,18 +23,22 @@ struct ExcelThemes {
     pub date: Format,
     pub time: Format,
     pub datetime: Format,
+    pub score: Format,
 }
 
 pub struct WorkbookWriter {
     workbook: Workbook,
     themes: ExcelThemes,
+    /// Timezone clock-in and clock-out times are written in
+    timezone: Tz,
 }
 
 impl WorkbookWriter {
-    pub fn new() -> Self {
+    pub fn new(timezone: Tz) -> Self {
         Self {
             workbook: Workbook::new(),
             themes: Self::build_themes(),
+            timezone,
         }
     }
 
@@ -51,6 +58,7 @@ impl WorkbookWriter {
         let date = standard.clone().set_num_format("mm/dd/yyyy");
         let time = standard.clone().set_num_format("h:mm AM/PM");
         let datetime = standard.clone().set_num_format("YYYY-MM-DD h:mm AM/PM");
+        let score = right_align.clone().set_num_format("0.00");
 
         ExcelThemes {
             standard,
@@ -61,6 +69,7 @@ impl WorkbookWriter {
             date,
             time,
             datetime,
+            score,
         }
     }
 
@@ -79,7 +88,11 @@ impl WorkbookWriter {
         worksheet.set_column_width(2, 48)?;
         worksheet.set_column_width(3, 36)?;
         // count, hours, miles, grat ok
-        worksheet.set_column_range_width(4, 13, 12)?;
+        worksheet.set_column_range_width(4, 15, 12)?;
+        worksheet.set_column_width(16, 24)?;
+        worksheet.set_column_range_width(17, 18, 12)?;
+        worksheet.set_column_width(19, 36)?;
+        worksheet.set_column_range_width(20, 22, 12)?;
         write_header_row(worksheet, 0, &self.themes.header)?;
 
         let mut row = 1;
@@ -106,13 +119,94 @@ impl WorkbookWriter {
             worksheet.write_number_with_format(row, 7, entry.order.grat, &self.themes.money)?;
             worksheet.write_string(row, 8, entry.order.origin.to_string())?;
             worksheet.write_string(row, 9, entry.order.event.to_string())?;
-            write_order_date(worksheet, row, 10, entry.order.ready, &self.themes.time)?;
+            write_order_time(worksheet, row, 10, entry.order.ready, &self.themes.time)?;
             worksheet.write_number_with_format(row, 11, entry.order.total, &self.themes.money)?;
 
             if let (Some(clock_in), Some(clock_out)) = (entry.suggested_in, entry.suggested_out) {
-                write_order_timestamp(worksheet, row, 12, clock_in, &self.themes.time)?;
-                write_order_timestamp(worksheet, row, 13, clock_out, &self.themes.time)?;
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    12,
+                    clock_in,
+                    self.timezone,
+                    &self.themes.time,
+                )?;
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    13,
+                    clock_out,
+                    self.timezone,
+                    &self.themes.time,
+                )?;
             }
+
+            if let Some(name_match) = entry.name_match {
+                let rule = match name_match.rule {
+                    NameRule::Roster => "Roster",
+                    NameRule::LastName => "Last name",
+                    NameRule::FirstName => "First name",
+                    NameRule::Fuzzy => "Fuzzy",
+                };
+                worksheet.write_string(row, 14, rule)?;
+                worksheet.write_number_with_format(
+                    row,
+                    15,
+                    name_match.score,
+                    &self.themes.score,
+                )?;
+            }
+
+            let outcome = match entry.outcome {
+                MatchOutcome::Matched => "Matched",
+                MatchOutcome::NoNameCandidate => "No name candidate",
+                MatchOutcome::NameButOutsideWindow => "Outside window",
+                MatchOutcome::SkippedInvalidDriver => "Skipped",
+                MatchOutcome::MissingReadyTime => "Missing ready time",
+                MatchOutcome::ShiftAlreadyConsumed => "Shift already used",
+                MatchOutcome::ShiftIgnored => "Shift ignored",
+                MatchOutcome::Pinned => "Pinned",
+                MatchOutcome::ForcedUnmatched => "Forced unmatched",
+            };
+            worksheet.write_string(row, 16, outcome)?;
+            if let Some(delta) = entry.delta_minutes {
+                worksheet.write_number_with_format(row, 17, delta, &self.themes.right_align)?;
+            }
+            worksheet.write_number_with_format(
+                row,
+                18,
+                entry.candidate_count as f64,
+                &self.themes.right_align,
+            )?;
+            worksheet.write_string(row, 19, entry.order.source.describe())?;
+
+            let role = match entry.order.role {
+                Role::Lead => "Lead",
+                Role::CoDriver => "Co-driver",
+                Role::Helper => "Helper",
+            };
+            worksheet.write_string(row, 20, role)?;
+
+            // Jobs mode: the shift the matched job was clocked under
+            if let Some(shift) = entry.shift {
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    21,
+                    shift.in_time,
+                    self.timezone,
+                    &self.themes.time,
+                )?;
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    22,
+                    shift.out_time,
+                    self.timezone,
+                    &self.themes.time,
+                )?;
+            }
+
             row += 1;
         }
 
@@ -130,11 +224,25 @@ impl WorkbookWriter {
         worksheet.set_column_width(1, 15)?;
         worksheet.set_column_width(2, 24)?;
         worksheet.set_column_width(3, 24)?;
+        worksheet.set_column_width(4, 24)?;
+        worksheet.set_column_width(5, 24)?;
+        worksheet.set_column_width(6, 36)?;
+        worksheet.set_column_range_width(7, 8, 24)?;
 
         worksheet.write_row_with_format(
             0,
             0,
-            vec!["First Name", "Last Name", "Clock In", "Clock Out"],
+            vec![
+                "First Name",
+                "Last Name",
+                "Clock In",
+                "Clock Out",
+                "Customer",
+                "Note",
+                "Source",
+                "Shift In",
+                "Shift Out",
+            ],
             &self.themes.header,
         )?;
 
@@ -158,8 +266,55 @@ impl WorkbookWriter {
                 entry.last_name.to_string(),
                 &self.themes.standard,
             )?;
-            write_order_timestamp(worksheet, row, 2, entry.in_time, &self.themes.datetime)?;
-            write_order_timestamp(worksheet, row, 3, entry.out_time, &self.themes.datetime)?;
+            write_order_timestamp(
+                worksheet,
+                row,
+                2,
+                entry.in_time,
+                self.timezone,
+                &self.themes.datetime,
+            )?;
+            write_order_timestamp(
+                worksheet,
+                row,
+                3,
+                entry.out_time,
+                self.timezone,
+                &self.themes.datetime,
+            )?;
+            worksheet.write_string_with_format(row, 4, &entry.customer, &self.themes.standard)?;
+            if entry.ignored {
+                worksheet.write_string_with_format(
+                    row,
+                    5,
+                    "Intentionally unmatched",
+                    &self.themes.standard,
+                )?;
+            }
+            worksheet.write_string_with_format(
+                row,
+                6,
+                entry.source.describe(),
+                &self.themes.standard,
+            )?;
+            if let Some(shift) = entry.shift {
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    7,
+                    shift.in_time,
+                    self.timezone,
+                    &self.themes.datetime,
+                )?;
+                write_order_timestamp(
+                    worksheet,
+                    row,
+                    8,
+                    shift.out_time,
+                    self.timezone,
+                    &self.themes.datetime,
+                )?;
+            }
 
             row += 1;
         }
@@ -167,6 +322,169 @@ impl WorkbookWriter {
         Ok(())
     }
 
+    pub fn write_run_info(
+        &mut self,
+        options: &MatchOptions,
+        dst_policy: DstPolicy,
+    ) -> Result<(), Error> {
+        let worksheet = self
+            .workbook
+            .add_worksheet()
+            .set_name("Run Info")
+            .context("Couldn't add run info sheet")?;
+
+        worksheet.set_column_width(0, 32)?;
+        worksheet.set_column_width(1, 12)?;
+
+        worksheet.write_row_with_format(0, 0, vec!["Setting", "Value"], &self.themes.header)?;
+
+        let mode = match options.mode {
+            MatchMode::Greedy => "Greedy",
+            MatchMode::Optimal => "Optimal",
+        };
+        worksheet.write_string_with_format(1, 0, "Match mode", &self.themes.standard)?;
+        worksheet.write_string_with_format(1, 1, mode, &self.themes.right_align)?;
+
+        worksheet.write_string_with_format(
+            2,
+            0,
+            "Clock in before ready (minutes)",
+            &self.themes.standard,
+        )?;
+        worksheet.write_number_with_format(
+            2,
+            1,
+            options.window.before_minutes as f64,
+            &self.themes.right_align,
+        )?;
+
+        worksheet.write_string_with_format(
+            3,
+            0,
+            "Clock in after ready (minutes)",
+            &self.themes.standard,
+        )?;
+        worksheet.write_number_with_format(
+            3,
+            1,
+            options.window.after_minutes as f64,
+            &self.themes.right_align,
+        )?;
+
+        worksheet.write_string_with_format(4, 0, "Fuzzy name threshold", &self.themes.standard)?;
+        match options.fuzzy_threshold {
+            Some(threshold) => {
+                worksheet.write_number_with_format(4, 1, threshold, &self.themes.score)?
+            }
+            None => worksheet.write_string_with_format(4, 1, "Off", &self.themes.right_align)?,
+        };
+
+        worksheet.write_string_with_format(5, 0, "Timezone", &self.themes.standard)?;
+        worksheet.write_string_with_format(5, 1, self.timezone.name(), &self.themes.right_align)?;
+
+        let dst = match dst_policy {
+            DstPolicy::Earliest => "Earliest",
+            DstPolicy::Latest => "Latest",
+            DstPolicy::Flag => "Flag for review",
+        };
+        worksheet.write_string_with_format(6, 0, "DST transition times", &self.themes.standard)?;
+        worksheet.write_string_with_format(6, 1, dst, &self.themes.right_align)?;
+
+        Ok(())
+    }
+
+    pub fn write_import_issues(&mut self, issues: &[ImportIssue]) -> Result<(), Error> {
+        let worksheet = self
+            .workbook
+            .add_worksheet()
+            .set_name("Import Issues")
+            .context("Couldn't add import issues sheet")?;
+
+        worksheet.set_column_width(0, 24)?;
+        worksheet.set_column_width(1, 18)?;
+        worksheet.set_column_width(2, 8)?;
+        worksheet.set_column_width(3, 20)?;
+        worksheet.set_column_width(4, 24)?;
+        worksheet.set_column_width(5, 48)?;
+
+        worksheet.write_row_with_format(
+            0,
+            0,
+            vec!["File", "Sheet", "Row", "Column", "Value", "Reason"],
+            &self.themes.header,
+        )?;
+
+        for (index, issue) in issues.iter().enumerate() {
+            let row = index as u32 + 1;
+
+            worksheet.write_string_with_format(row, 0, &issue.file, &self.themes.standard)?;
+            worksheet.write_string_with_format(row, 1, &issue.sheet, &self.themes.standard)?;
+            worksheet.write_number_with_format(
+                row,
+                2,
+                issue.row as f64,
+                &self.themes.right_align,
+            )?;
+            worksheet.write_string_with_format(row, 3, &issue.column, &self.themes.standard)?;
+            worksheet.write_string_with_format(row, 4, &issue.value, &self.themes.standard)?;
+            worksheet.write_string_with_format(row, 5, &issue.reason, &self.themes.standard)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_findings(&mut self, findings: &[Finding]) -> Result<(), Error> {
+        let worksheet = self
+            .workbook
+            .add_worksheet()
+            .set_name("Checks")
+            .context("Couldn't add checks sheet")?;
+
+        worksheet.set_column_width(0, 10)?;
+        worksheet.set_column_width(1, 24)?;
+        worksheet.set_column_width(2, 18)?;
+        worksheet.set_column_width(3, 8)?;
+        worksheet.set_column_width(4, 64)?;
+
+        worksheet.write_row_with_format(
+            0,
+            0,
+            vec!["Severity", "File", "Sheet", "Row", "Problem"],
+            &self.themes.header,
+        )?;
+
+        for (index, finding) in findings.iter().enumerate() {
+            let row = index as u32 + 1;
+
+            let severity = match finding.severity {
+                Severity::Warning => "Warning",
+                Severity::Error => "Error",
+            };
+            worksheet.write_string_with_format(row, 0, severity, &self.themes.standard)?;
+            worksheet.write_string_with_format(
+                row,
+                1,
+                &finding.source.file,
+                &self.themes.standard,
+            )?;
+            worksheet.write_string_with_format(
+                row,
+                2,
+                &finding.source.sheet,
+                &self.themes.standard,
+            )?;
+            worksheet.write_number_with_format(
+                row,
+                3,
+                finding.source.row as f64,
+                &self.themes.right_align,
+            )?;
+            worksheet.write_string_with_format(row, 4, &finding.message, &self.themes.standard)?;
+        }
+
+        Ok(())
+    }
+
     pub fn save(&mut self, path: &PathBuf) -> Result<(), anyhow::Error> {
         self.workbook.save(&path).context("Couldn't save workbook")
     }
@@ -5,8 +5,10 @@ import { open } from "@tauri-apps/plugin-dialog";
 import { FileCheck, Loader, TriangleAlert, Upload } from "lucide-react";
 import { useDropZone } from "./dropzone.ts";
 
+const EXTENSIONS = ["xlsx", "xlsm", "xlsb", "xls", "ods", "csv"];
+
 type FileDropProps = {
-  mutation: UseMutationResult<string, Error, string, unknown>;
+  mutation: UseMutationResult<string, Error, string[], unknown>;
   reset: () => void;
   title: string;
 };
@@ -17,28 +19,30 @@ export function FileDropButton(props: FileDropProps) {
   const { ref } = useDropZone({ onDrop: handleDrop });
 
   async function handleClick(): Promise<void> {
-    const file = await open({
-      multiple: false,
+    const files = await open({
+      multiple: true,
       directory: false,
       defaultPath: await desktopDir(),
-      filters: [{ name: "Excel", extensions: ["xlsx"] }],
+      filters: [{ name: "Spreadsheet", extensions: EXTENSIONS }],
     });
 
-    if (file) {
-      sendIt(file);
+    if (files) {
+      sendIt(files);
     }
   }
 
   function handleDrop(paths: string[]): void {
-    const filePath = paths[0];
-
-    sendIt(filePath);
+    sendIt(paths);
   }
 
-  function sendIt(filePath: string): void {
-    if (!filePath.endsWith(".xlsx")) return;
+  function sendIt(filePaths: string[]): void {
+    const supported = filePaths.filter((filePath) => {
+      const extension = filePath.split(".").pop()?.toLowerCase() ?? "";
+      return EXTENSIONS.includes(extension);
+    });
+    if (supported.length === 0) return;
 
-    ourMut.mutate(filePath);
+    ourMut.mutate(supported);
     reset();
   }
 
@@ -0,0 +1,54 @@
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip.tsx";
+import { IntuitMode, useIntuitMode } from "../hooks.ts";
+import { useGetSettings } from "../pages/home/api.ts";
+
+const INTUIT_MODES: { value: IntuitMode; label: string }[] = [
+  { value: "shifts", label: "Whole shifts" },
+  { value: "jobs", label: "Jobs in a shift" },
+];
+
+type Props = {
+  /** Called after a change, since the linked timesheet was read in the old mode */
+  onChange: () => void;
+};
+
+export function IntuitModeSelect(props: Props) {
+  const settings = useGetSettings();
+  const [mode, setMode] = useIntuitMode();
+
+  return (
+    <div className="flex flex-col gap-2 items-center justify-center">
+      <Tooltip delayDuration={500}>
+        <TooltipTrigger>
+          <span>Timesheet</span>
+        </TooltipTrigger>
+        <TooltipContent>
+          <p>
+            Whole shifts match each shift total to one order.<br />Jobs in a
+            shift match each job row on its own, for drivers with several
+            deliveries per shift.<br />Link the Intuit file again after
+            changing this.
+          </p>
+        </TooltipContent>
+      </Tooltip>
+      <select
+        className="w-44 rounded border bg-transparent px-1"
+        value={mode ?? settings.data.intuitMode}
+        onChange={(event) => {
+          setMode(event.target.value as IntuitMode);
+          props.onChange();
+        }}
+      >
+        {INTUIT_MODES.map((option) => (
+          <option key={option.value} value={option.value}>
+            {option.label}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+}
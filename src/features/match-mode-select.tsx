@@ -0,0 +1,57 @@
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip.tsx";
+import { Dispatch, SetStateAction } from "react";
+import { MatchMode, SubmitOptions } from "../hooks.ts";
+import { useGetSettings } from "../pages/home/api.ts";
+
+const MATCH_MODES: { value: MatchMode; label: string }[] = [
+  { value: "greedy", label: "First fit" },
+  { value: "optimal", label: "Best overall" },
+];
+
+type Props = {
+  options: SubmitOptions;
+  setOptions: Dispatch<SetStateAction<SubmitOptions>>;
+};
+
+export function MatchModeSelect(props: Props) {
+  const { options, setOptions } = props;
+  const settings = useGetSettings();
+
+  const matchMode = options.matchMode ?? settings.data.matchMode;
+
+  return (
+    <div className="flex flex-col gap-2 items-center justify-center">
+      <Tooltip delayDuration={500}>
+        <TooltipTrigger>
+          <span>Matching</span>
+        </TooltipTrigger>
+        <TooltipContent>
+          <p>
+            First fit gives each order the first open shift, in file order.
+            <br />Best overall pairs orders and shifts by the closest clock-in
+            times, preferring exact names.
+          </p>
+        </TooltipContent>
+      </Tooltip>
+      <select
+        className="w-44 rounded border bg-transparent px-1"
+        value={matchMode}
+        onChange={(event) =>
+          setOptions((current) => ({
+            ...current,
+            matchMode: event.target.value as MatchMode,
+          }))}
+      >
+        {MATCH_MODES.map((mode) => (
+          <option key={mode.value} value={mode.value}>
+            {mode.label}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+}
@@ -0,0 +1,69 @@
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip.tsx";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Dispatch, SetStateAction } from "react";
+import { SubmitOptions } from "../hooks.ts";
+import { useGetSettings } from "../pages/home/api.ts";
+
+type Props = {
+  options: SubmitOptions;
+  setOptions: Dispatch<SetStateAction<SubmitOptions>>;
+};
+
+export function OutputSelect(props: Props) {
+  const { options, setOptions } = props;
+  const settings = useGetSettings();
+
+  const outputDir = options.outputDir ?? settings.data.outputDir;
+  const filenameTemplate = options.filenameTemplate ??
+    settings.data.filenameTemplate;
+
+  async function handleFolder(): Promise<void> {
+    const folder = await open({
+      directory: true,
+      multiple: false,
+      defaultPath: outputDir ?? undefined,
+    });
+
+    if (folder) {
+      setOptions((current) => ({ ...current, outputDir: folder }));
+    }
+  }
+
+  return (
+    <div className="flex flex-col gap-2 items-center justify-center">
+      <Tooltip delayDuration={500}>
+        <TooltipTrigger>
+          <span>Output</span>
+        </TooltipTrigger>
+        <TooltipContent>
+          <p>
+            Folder and file name for the formatted workbook.<br />
+            {"{start}"} and {"{end}"} become the first and last order dates.
+            <br />Both are remembered for the next run.
+          </p>
+        </TooltipContent>
+      </Tooltip>
+      <button
+        type="button"
+        className="w-44 rounded border bg-transparent px-1 truncate text-left"
+        title={outputDir ?? "Documents"}
+        onClick={handleFolder}
+      >
+        {outputDir ?? "Documents"}
+      </button>
+      <input
+        className="w-44 rounded border bg-transparent px-1"
+        value={filenameTemplate}
+        onChange={(event) =>
+          setOptions((current) => ({
+            ...current,
+            filenameTemplate: event.target.value,
+          }))}
+      />
+    </div>
+  );
+}
@@ -0,0 +1,95 @@
+import {
+  Tooltip,
+  TooltipContent,
+  TooltipTrigger,
+} from "@/components/ui/tooltip.tsx";
+import {
+  DstPolicy,
+  useGetSettings,
+  useTimezoneMutation,
+} from "../pages/home/api.ts";
+
+const TIMEZONES = [
+  "America/New_York",
+  "America/Chicago",
+  "America/Denver",
+  "America/Phoenix",
+  "America/Los_Angeles",
+  "America/Anchorage",
+  "Pacific/Honolulu",
+];
+
+const DST_POLICIES: { value: DstPolicy; label: string }[] = [
+  { value: "earliest", label: "Earlier instant" },
+  { value: "latest", label: "Later instant" },
+  { value: "flag", label: "Flag for review" },
+];
+
+type Props = {
+  /** Called when a change unlinked the files, since they were read with the old setting */
+  onUnlink: () => void;
+};
+
+export function TimezoneSelect(props: Props) {
+  const settings = useGetSettings();
+  const timezoneMut = useTimezoneMutation();
+
+  const { timezone, dstPolicy } = settings.data;
+  const timezones = TIMEZONES.includes(timezone)
+    ? TIMEZONES
+    : [timezone, ...TIMEZONES];
+
+  function save(changed: Partial<typeof settings.data>): void {
+    timezoneMut.mutate(
+      { timezone, dstPolicy, ...changed },
+      {
+        onSuccess: (unlinked) => {
+          if (unlinked) props.onUnlink();
+        },
+      },
+    );
+  }
+
+  return (
+    <div className="flex flex-col gap-2 items-center justify-center">
+      <Tooltip delayDuration={500}>
+        <TooltipTrigger>
+          <span>Timezone</span>
+        </TooltipTrigger>
+        <TooltipContent>
+          <p>
+            Where the kitchen is. Order and clock times are read in this
+            timezone.<br />Times in the hour the clocks change are read as
+            chosen below.<br />Changing either unlinks the files, so link
+            them again.
+          </p>
+        </TooltipContent>
+      </Tooltip>
+      <select
+        className="w-44 rounded border bg-transparent px-1"
+        value={timezone}
+        disabled={timezoneMut.isPending}
+        onChange={(event) => save({ timezone: event.target.value })}
+      >
+        {timezones.map((name) => (
+          <option key={name} value={name}>
+            {name.replace("_", " ")}
+          </option>
+        ))}
+      </select>
+      <select
+        className="w-44 rounded border bg-transparent px-1"
+        value={dstPolicy}
+        disabled={timezoneMut.isPending}
+        onChange={(event) =>
+          save({ dstPolicy: event.target.value as DstPolicy })}
+      >
+        {DST_POLICIES.map((policy) => (
+          <option key={policy.value} value={policy.value}>
+            {policy.label}
+          </option>
+        ))}
+      </select>
+    </div>
+  );
+}
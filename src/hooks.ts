@@ -18,3 +18,29 @@ const precisionAtom = atom(1);
 export function usePrecision() {
   return useAtom(precisionAtom);
 }
+
+/** Options sent with submit. Anything left unset falls back to the saved settings */
+export type SubmitOptions = {
+  outputDir?: string;
+  filenameTemplate?: string;
+  matchMode?: MatchMode;
+};
+
+/** How orders are paired with shifts, see compare/types.rs */
+export type MatchMode = "greedy" | "optimal";
+
+/** Which Intuit rows become time activities, see deserialize/types.rs */
+export type IntuitMode = "shifts" | "jobs";
+
+const intuitModeAtom = atom<IntuitMode | undefined>(undefined);
+
+/** Mode sent with the next Intuit file. Unset uses the saved setting */
+export function useIntuitMode() {
+  return useAtom(intuitModeAtom);
+}
+
+const submitOptionsAtom = atom<SubmitOptions>({});
+
+export function useSubmitOptions() {
+  return useAtom(submitOptionsAtom);
+}
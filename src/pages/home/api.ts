@@ -1,6 +1,18 @@
-import { useMutation, useSuspenseQuery } from "@tanstack/react-query";
+import {
+  useMutation,
+  useQueryClient,
+  useSuspenseQuery,
+} from "@tanstack/react-query";
 import { invoke } from "@tauri-apps/api/core";
-import { Page, useSimpleRouter } from "../../hooks.ts";
+import { ask } from "@tauri-apps/plugin-dialog";
+import {
+  IntuitMode,
+  MatchMode,
+  Page,
+  SubmitOptions,
+  useIntuitMode,
+  useSimpleRouter,
+} from "../../hooks.ts";
 import { generateStatsData, useStatsData } from "../review/data.ts";
 
 type ExpectedHeaders = {
@@ -15,17 +27,124 @@ export function useGetHeaders() {
   });
 }
 
+/// See timezone.rs
+export type DstPolicy = "earliest" | "latest" | "flag";
+
+/// The saved settings this page reads, see settings.rs
+type Settings = {
+  outputDir: string | null;
+  filenameTemplate: string;
+  matchMode: MatchMode;
+  timezone: string;
+  dstPolicy: DstPolicy;
+  intuitMode: IntuitMode;
+};
+
+export function useGetSettings() {
+  return useSuspenseQuery({
+    queryKey: ["settings"],
+    queryFn: () => invoke<Settings>("get_settings"),
+  });
+}
+
+export function useTimezoneMutation() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: (settings: Pick<Settings, "timezone" | "dstPolicy">) =>
+      invoke<boolean>("set_timezone", {
+        timezone: settings.timezone,
+        dstPolicy: settings.dstPolicy,
+      }),
+    onSuccess: () => queryClient.invalidateQueries({ queryKey: ["settings"] }),
+  });
+}
+
+/// See deserialize/types.rs
+export type ImportIssue = {
+  file: string;
+  sheet: string;
+  row: number;
+  column: string;
+  value: string;
+  reason: string;
+};
+
+/// See validate.rs
+export type Finding = {
+  severity: "warning" | "error";
+  source: { file: string; sheet: string; row: number };
+  message: string;
+};
+
+/// First and last dates as YYYY-MM-DD, see handlers.rs
+export type PayPeriods = {
+  orders: [string, string] | null;
+  timesheet: [string, string] | null;
+  warning: string | null;
+};
+
+type ImportResult = {
+  fileName: string;
+  records: number;
+  issues: ImportIssue[];
+};
+
+/// File names to show on the drop button, noting how many rows had import issues.
+/// Issues are per cell, so a row with several bad cells is counted once
+function describeImport(results: ImportResult[]): string {
+  const rows = new Set(
+    results.flatMap((result) =>
+      result.issues.map((issue) =>
+        JSON.stringify([issue.file, issue.sheet, issue.row])
+      )
+    ),
+  );
+  const count = rows.size;
+  const name = results.length === 1
+    ? results[0].fileName
+    : `${results.length} files`;
+  if (count === 0) return name;
+
+  return `${name} (${count} ${count === 1 ? "row" : "rows"} with issues)`;
+}
+
+/// Links the files in order. The first replaces what was linked, the rest are appended
+async function linkFiles(
+  command: string,
+  filePaths: string[],
+  args: Record<string, unknown> = {},
+): Promise<ImportResult[]> {
+  const results: ImportResult[] = [];
+  for (const [index, filePath] of filePaths.entries()) {
+    results.push(
+      await invoke<ImportResult>(command, {
+        ...args,
+        filePath,
+        append: index > 0,
+      }),
+    );
+  }
+
+  return results;
+}
+
 export function useCatereaseMutation() {
   return useMutation({
-    mutationFn: (filePath: string) =>
-      invoke<string>("caterease_input", { filePath }),
+    mutationFn: async (filePaths: string[]) =>
+      describeImport(await linkFiles("caterease_input", filePaths)),
   });
 }
 
 export function useIntuitMutation() {
+  const queryClient = useQueryClient();
+  const [mode] = useIntuitMode();
+
   return useMutation({
-    mutationFn: (filePath: string) =>
-      invoke<string>("intuit_input", { filePath }),
+    mutationFn: async (filePaths: string[]) =>
+      describeImport(await linkFiles("intuit_input", filePaths, { mode })),
+    // The mode sent is saved as the new default
+    onSuccess: () => queryClient.invalidateQueries({ queryKey: ["settings"] }),
   });
 }
 
@@ -46,16 +165,51 @@ export type ProcessResult = {
   highest_late_percent: number;
   latest_clock_in_driver: string;
   latest_clock_in_diff_minutes: number;
+  import_issues: number;
+  findings: Finding[];
+  pay_periods: PayPeriods;
+};
+
+/// See handlers.rs. Nothing is written when the output file already exists
+type SubmitResult =
+  | ({ status: "written" } & ProcessResult)
+  | { status: "exists"; path: string };
+
+type SubmitVariables = {
+  precision: number;
+  options: SubmitOptions;
 };
 
 export function useSubmitMutation() {
+  const queryClient = useQueryClient();
   const [, setPage] = useSimpleRouter();
   const [, setStatsData] = useStatsData();
 
   return useMutation({
-    mutationFn: (precision: number) =>
-      invoke<ProcessResult>("submit", { precision }),
+    mutationFn: async ({ precision, options }: SubmitVariables) => {
+      const result = await invoke<SubmitResult>("submit", {
+        precision,
+        options,
+      });
+      if (result.status === "written") return result;
+
+      const message = `Output file already exists: ${result.path}`;
+      const overwrite = await ask(`${message}\n\nReplace it?`, {
+        title: "Overwrite",
+        kind: "warning",
+      });
+      if (!overwrite) throw new Error(message);
+
+      const replaced = await invoke<SubmitResult>("submit", {
+        precision,
+        options: { ...options, overwrite },
+      });
+      if (replaced.status !== "written") throw new Error(message);
+
+      return replaced;
+    },
     onSuccess: (data) => {
+      queryClient.invalidateQueries({ queryKey: ["settings"] });
       setStatsData(generateStatsData(data));
       setPage(Page.Review);
     },
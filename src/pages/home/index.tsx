@@ -14,8 +14,12 @@ import { PurpleBg } from "../../features/animated-bg/purple-bg.tsx";
 import { Stars } from "../../features/animated-bg/stars.tsx";
 import { FileDropButton } from "../../features/file-drop/file-drop-button.tsx";
 import { FileDropDialog } from "../../features/file-drop/file-drop-dialog.tsx";
+import { IntuitModeSelect } from "../../features/intuit-mode-select.tsx";
+import { MatchModeSelect } from "../../features/match-mode-select.tsx";
+import { OutputSelect } from "../../features/output-select.tsx";
 import { PrecisionSlider } from "../../features/precision-slider.tsx";
-import { usePrecision } from "../../hooks.ts";
+import { TimezoneSelect } from "../../features/timezone-select.tsx";
+import { usePrecision, useSubmitOptions } from "../../hooks.ts";
 import {
   useCatereaseMutation,
   useGetHeaders,
@@ -27,6 +31,7 @@ import { ResultSection } from "./result-section.tsx";
 
 export function HomePage() {
   const [precision, setPrecision] = usePrecision();
+  const [submitOptions, setSubmitOptions] = useSubmitOptions();
 
   const expectedHeaders = useGetHeaders();
 
@@ -107,6 +112,16 @@ export function HomePage() {
                 precision={precision}
                 setPrecision={setPrecision}
               />
+              <IntuitModeSelect onChange={intuitMut.reset} />
+              <MatchModeSelect
+                options={submitOptions}
+                setOptions={setSubmitOptions}
+              />
+              <TimezoneSelect onUnlink={reset} />
+              <OutputSelect
+                options={submitOptions}
+                setOptions={setSubmitOptions}
+              />
             </div>
             <div className="flex-1">
               {errors.length > 0 && (
@@ -120,7 +135,8 @@ export function HomePage() {
         <CardFooter className="flex justify-end">
           <ResultSection
             mutation={submitMut}
-            onSubmit={() => submitMut.mutate(precision)}
+            onSubmit={() =>
+              submitMut.mutate({ precision, options: submitOptions })}
             ready={ready}
             reset={reset}
             showReset={errors.length > 0}
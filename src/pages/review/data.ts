@@ -42,6 +42,10 @@ export function generateStatsData(result: ProcessResult): StatCard[] {
         `${result.expanded} Multiples Expanded`,
         `${result.matched} Matched`,
         `${result.skipped} Skipped/Takeout`,
+        ...(result.findings.length > 0
+          ? [`${result.findings.length} Warnings to Review`]
+          : []),
+        ...(result.pay_periods.warning ? [result.pay_periods.warning] : []),
       ].join("\n"),
     },
     {